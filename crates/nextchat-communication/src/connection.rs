@@ -2,7 +2,8 @@
 //!
 //! This module contains the socket connection structure.
 
-use nextchat_database::Uuid;
+use chrono::Utc;
+use nextchat_database::{NaiveDateTime, Uuid};
 use tokio::sync::mpsc::{self, error::SendError};
 use warp::{ws::Message, Error};
 
@@ -13,15 +14,19 @@ type Socket = mpsc::UnboundedSender<Result<Message, Error>>;
 #[derive(Clone)]
 pub struct Connection {
     user_id: Uuid,
+    session_id: Uuid,
     socket: Socket,
+    connected_at: NaiveDateTime,
 }
 
 impl Connection {
     /// Create a new socket connection object.
-    pub fn new(user_id: &Uuid, socket: &Socket) -> Self {
+    pub fn new(user_id: &Uuid, session_id: &Uuid, socket: &Socket) -> Self {
         Self {
             user_id: user_id.clone(),
+            session_id: session_id.clone(),
             socket: socket.clone(),
+            connected_at: Utc::now().naive_utc(),
         }
     }
 
@@ -30,6 +35,16 @@ impl Connection {
         self.user_id
     }
 
+    /// Get the id of the session used to open the connection.
+    pub fn get_session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    /// Get the timestamp when the connection was opened.
+    pub fn get_connected_at(&self) -> NaiveDateTime {
+        self.connected_at
+    }
+
     /// Send a text message using the websocket connection.
     pub fn send_text_message(
         &self,
@@ -45,4 +60,9 @@ impl Connection {
     ) -> Result<(), SendError<Result<Message, Error>>> {
         self.send_text_message(&packet.to_message().to_string())
     }
+
+    /// Send a close message using the websocket connection.
+    pub fn close(&self) -> Result<(), SendError<Result<Message, Error>>> {
+        self.socket.send(Ok(Message::close()))
+    }
 }
@@ -1,12 +1,15 @@
 //! NextChat Communication inconming module.
 
 mod friend_request;
+mod link_confirm;
 
 use async_trait::async_trait;
 use nextchat_database::Client;
 
 use super::{CommunicationMessage, Connection, StorageType};
 
+pub use friend_request::{FriendRequestHandler, FriendRequestHandlerType};
+
 #[async_trait]
 pub trait PacketEvent {
     async fn run(
@@ -27,6 +30,9 @@ pub async fn run_event(
         "friend_request" => {
             friend_request::FriendRequestEvent::run(connection, message, client, storage).await
         }
+        "link_confirm" => {
+            link_confirm::LinkConfirmEvent::run(connection, message, client, storage).await
+        }
         _ => {
             println!("Unknown event: {}", message.to_string());
         }
@@ -1,22 +1,64 @@
 //! NextChat Communication friend request event module.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use nextchat_database::Client;
+use nextchat_database::{Client, Uuid};
 
-use crate::{CommunicationMessage, Connection, StorageType};
+use crate::{CommunicationMessage, Connection, FriendRequestResultComposer, StorageType};
 
 use super::PacketEvent;
 
+/// Handler of the friend requests registered in the storage, it shares the friend
+/// requests of the `friend_request` event with the HTTP API.
+#[async_trait]
+pub trait FriendRequestHandler: Send + Sync {
+    /// Send a friend request from the transmitter to the receiver and notify the
+    /// receiver, or get the error code sent by the `friend_request_result` packet.
+    async fn send_friend_request(
+        &self,
+        client: &Client,
+        storage: &StorageType,
+        transmitter: &Uuid,
+        receiver: &Uuid,
+    ) -> Result<(), String>;
+}
+
+pub type FriendRequestHandlerType = Arc<dyn FriendRequestHandler>;
+
+/// Event sent by an authenticated connection to send a friend request to a user.
+///
+/// `/friend_request {user_id}`
+///
+/// Responds with `/friend_request_result {user_id} true`, or
+/// `/friend_request_result {user_id} false {error_code}` with the code of the
+/// handler, or `invalid_user_id` when the user id cannot be parsed.
 pub struct FriendRequestEvent;
 
 #[async_trait]
 impl PacketEvent for FriendRequestEvent {
     async fn run(
-        _connection: &Connection,
+        connection: &Connection,
         message: &CommunicationMessage,
-        _client: &Client,
-        _storage: &StorageType,
+        client: &Client,
+        storage: &StorageType,
     ) {
-        println!("Friend Request Event: {}", message.to_string())
+        let argument: String = message.get_arguments().first().cloned().unwrap_or_default();
+
+        // The storage is not locked while the handler runs, it notifies the receiver.
+        let handler = storage.read().await.get_friend_request_handler();
+
+        let error: Option<String> = match (Uuid::parse_str(&argument), handler) {
+            (Err(_), _) => Some(String::from("invalid_user_id")),
+            (Ok(_), None) => Some(String::from("unknown")),
+            (Ok(user_id), Some(handler)) => handler
+                .send_friend_request(client, storage, &connection.get_user_id(), &user_id)
+                .await
+                .err(),
+        };
+
+        connection
+            .send_packet(Box::new(FriendRequestResultComposer::new(argument, error)))
+            .ok();
     }
 }
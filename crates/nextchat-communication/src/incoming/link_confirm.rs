@@ -0,0 +1,39 @@
+//! NextChat Communication link confirm event module.
+
+use async_trait::async_trait;
+use nextchat_database::Client;
+
+use crate::{CommunicationMessage, Connection, LinkConfirmedComposer, StorageType};
+
+use super::PacketEvent;
+
+/// Event sent by an authenticated connection to sign in a waiting device with
+/// the pairing token shown in its QR code.
+///
+/// `/link_confirm {pairing_token}`
+pub struct LinkConfirmEvent;
+
+#[async_trait]
+impl PacketEvent for LinkConfirmEvent {
+    async fn run(
+        connection: &Connection,
+        message: &CommunicationMessage,
+        _client: &Client,
+        storage: &StorageType,
+    ) {
+        let pending_link = match message.get_arguments().first() {
+            Some(pairing_token) => storage.write().await.take_pending_link(pairing_token),
+            None => None,
+        };
+
+        // The waiting connection creates the session when it receives the user id.
+        let confirmed: bool = match pending_link {
+            Some(sender) => sender.send(connection.get_user_id()).is_ok(),
+            None => false,
+        };
+
+        connection
+            .send_packet(Box::new(LinkConfirmedComposer::new(confirmed)))
+            .ok();
+    }
+}
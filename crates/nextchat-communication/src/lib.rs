@@ -6,8 +6,13 @@ mod outgoing;
 mod storage;
 
 pub use connection::Connection;
-pub use incoming::run_event;
-pub use storage::{Storage, StorageType};
+pub use incoming::{run_event, FriendRequestHandler, FriendRequestHandlerType};
+pub use outgoing::{
+    AnnouncementComposer, FriendRequestReceivedComposer, FriendRequestResultComposer,
+    LinkConfirmedComposer, LinkSessionComposer, LinkTokenComposer, PacketComposer,
+    SessionClosedComposer,
+};
+pub use storage::{LinkSender, Storage, StorageType};
 
 #[derive(Debug)]
 pub struct CommunicationMessage {
@@ -16,6 +21,14 @@ pub struct CommunicationMessage {
 }
 
 impl CommunicationMessage {
+    /// Create a new communication message.
+    pub fn new(name: &str, arguments: Vec<String>) -> Self {
+        Self {
+            name: String::from(name),
+            arguments,
+        }
+    }
+
     /// Parse a string like `/{name} {argument1} {argument2}` to a CommunicationMessage struct.
     pub fn from_string(message: String) -> Result<Self, String> {
         // Check if the messages does not start with a slash (`/`).
@@ -1,7 +1,23 @@
 //! NextChat Communication outgoing module.
 
+mod announcement;
+mod friend_request_received;
+mod friend_request_result;
+mod link_confirmed;
+mod link_session;
+mod link_token;
+mod session_closed;
+
 use super::CommunicationMessage;
 
+pub use announcement::AnnouncementComposer;
+pub use friend_request_received::FriendRequestReceivedComposer;
+pub use friend_request_result::FriendRequestResultComposer;
+pub use link_confirmed::LinkConfirmedComposer;
+pub use link_session::LinkSessionComposer;
+pub use link_token::LinkTokenComposer;
+pub use session_closed::SessionClosedComposer;
+
 pub trait PacketComposer {
     fn to_message(&self) -> CommunicationMessage;
 }
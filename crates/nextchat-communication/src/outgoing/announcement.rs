@@ -0,0 +1,25 @@
+//! NextChat Communication announcement packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to all connections with a system announcement.
+///
+/// `/announcement {message}`
+pub struct AnnouncementComposer {
+    message: String,
+}
+
+impl AnnouncementComposer {
+    /// Create a new announcement packet.
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl PacketComposer for AnnouncementComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new("announcement", [self.message.clone()].to_vec())
+    }
+}
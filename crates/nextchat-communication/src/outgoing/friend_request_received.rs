@@ -0,0 +1,29 @@
+//! NextChat Communication friend request received packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to the connections of a user when a friend request is received.
+///
+/// `/friend_request_received {user_id} {username}`
+pub struct FriendRequestReceivedComposer {
+    user_id: String,
+    username: String,
+}
+
+impl FriendRequestReceivedComposer {
+    /// Create a new friend request received packet.
+    pub fn new(user_id: String, username: String) -> Self {
+        Self { user_id, username }
+    }
+}
+
+impl PacketComposer for FriendRequestReceivedComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new(
+            "friend_request_received",
+            [self.user_id.clone(), self.username.clone()].to_vec(),
+        )
+    }
+}
@@ -0,0 +1,38 @@
+//! NextChat Communication friend request result packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to the connection that sent a friend request with the result.
+///
+/// `/friend_request_result {user_id} true`
+/// `/friend_request_result {user_id} false {error_code}`
+pub struct FriendRequestResultComposer {
+    user_id: String,
+    error_code: Option<String>,
+}
+
+impl FriendRequestResultComposer {
+    /// Create a new friend request result packet, without error code when the
+    /// request was sent.
+    pub fn new(user_id: String, error_code: Option<String>) -> Self {
+        Self {
+            user_id,
+            error_code,
+        }
+    }
+}
+
+impl PacketComposer for FriendRequestResultComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        let mut arguments: Vec<String> =
+            [self.user_id.clone(), self.error_code.is_none().to_string()].to_vec();
+
+        if let Some(error_code) = &self.error_code {
+            arguments.push(error_code.clone());
+        }
+
+        CommunicationMessage::new("friend_request_result", arguments)
+    }
+}
@@ -0,0 +1,25 @@
+//! NextChat Communication link confirmed packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to the connection that confirmed a link with the result.
+///
+/// `/link_confirmed {true|false}`
+pub struct LinkConfirmedComposer {
+    confirmed: bool,
+}
+
+impl LinkConfirmedComposer {
+    /// Create a new link confirmed packet.
+    pub fn new(confirmed: bool) -> Self {
+        Self { confirmed }
+    }
+}
+
+impl PacketComposer for LinkConfirmedComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new("link_confirmed", [self.confirmed.to_string()].to_vec())
+    }
+}
@@ -0,0 +1,39 @@
+//! NextChat Communication link session packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to a linked device with the tokens of its new session.
+///
+/// `/link_session {user_id} {access_token} {refresh_token}`
+pub struct LinkSessionComposer {
+    user_id: String,
+    access_token: String,
+    refresh_token: String,
+}
+
+impl LinkSessionComposer {
+    /// Create a new link session packet.
+    pub fn new(user_id: String, access_token: String, refresh_token: String) -> Self {
+        Self {
+            user_id,
+            access_token,
+            refresh_token,
+        }
+    }
+}
+
+impl PacketComposer for LinkSessionComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new(
+            "link_session",
+            [
+                self.user_id.clone(),
+                self.access_token.clone(),
+                self.refresh_token.clone(),
+            ]
+            .to_vec(),
+        )
+    }
+}
@@ -0,0 +1,33 @@
+//! NextChat Communication link token packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to a device waiting to be linked with the pairing token for its
+/// QR code and the seconds until it expires.
+///
+/// `/link_token {pairing_token} {expires_in}`
+pub struct LinkTokenComposer {
+    pairing_token: String,
+    expires_in: u64,
+}
+
+impl LinkTokenComposer {
+    /// Create a new link token packet.
+    pub fn new(pairing_token: String, expires_in: u64) -> Self {
+        Self {
+            pairing_token,
+            expires_in,
+        }
+    }
+}
+
+impl PacketComposer for LinkTokenComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new(
+            "link_token",
+            [self.pairing_token.clone(), self.expires_in.to_string()].to_vec(),
+        )
+    }
+}
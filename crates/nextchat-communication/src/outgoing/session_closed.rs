@@ -0,0 +1,25 @@
+//! NextChat Communication session closed packet module.
+
+use crate::CommunicationMessage;
+
+use super::PacketComposer;
+
+/// Packet sent to a connection before closing it because its session was revoked.
+///
+/// `/session_closed {session_id}`
+pub struct SessionClosedComposer {
+    session_id: String,
+}
+
+impl SessionClosedComposer {
+    /// Create a new session closed packet.
+    pub fn new(session_id: String) -> Self {
+        Self { session_id }
+    }
+}
+
+impl PacketComposer for SessionClosedComposer {
+    fn to_message(&self) -> CommunicationMessage {
+        CommunicationMessage::new("session_closed", [self.session_id.clone()].to_vec())
+    }
+}
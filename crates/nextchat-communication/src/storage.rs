@@ -2,17 +2,47 @@
 //!
 //! This module contains the storage structure for manage the user connections.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use nextchat_database::Uuid;
-use nextchat_utils::AppVersions;
-use tokio::sync::RwLock;
+use nextchat_utils::{AppVersions, LoginAttempts};
+use tokio::sync::{oneshot, RwLock};
 
-use crate::Connection;
+use crate::{Connection, FriendRequestHandlerType, SessionClosedComposer};
+
+/// Failed signin attempts of an account before locking it.
+const ACCOUNT_FREE_ATTEMPTS: u32 = 5;
+
+/// Failed signin attempts of an IP address before locking it.
+const IP_FREE_ATTEMPTS: u32 = 20;
+
+/// Max devices waiting to be linked, the link connections are not authenticated.
+const MAX_PENDING_LINKS: usize = 1000;
+
+/// Max devices waiting to be linked from the same IP address.
+const MAX_PENDING_LINKS_PER_IP: usize = 5;
+
+/// Sender of the user id that confirmed a pending link.
+pub type LinkSender = oneshot::Sender<Uuid>;
+
+struct PendingLink {
+    sender: LinkSender,
+    ip: String,
+    expires_at: Instant,
+}
 
 pub struct Storage {
     connections: HashMap<Uuid, Connection>,
+    pending_links: HashMap<String, PendingLink>,
+    pending_links_by_ip: HashMap<String, usize>,
     versions: AppVersions,
+    account_attempts: LoginAttempts,
+    ip_attempts: LoginAttempts,
+    friend_request_handler: Option<FriendRequestHandlerType>,
 }
 
 pub type StorageType = Arc<RwLock<Storage>>;
@@ -36,22 +66,170 @@ impl Storage {
     pub fn default() -> StorageType {
         Arc::new(RwLock::new(Self {
             connections: HashMap::new(),
+            pending_links: HashMap::new(),
+            pending_links_by_ip: HashMap::new(),
             versions: AppVersions::default(),
+            account_attempts: LoginAttempts::new(ACCOUNT_FREE_ATTEMPTS),
+            ip_attempts: LoginAttempts::new(IP_FREE_ATTEMPTS),
+            friend_request_handler: None,
         }))
     }
 
     /// Add a new connection to the connections list.
-    pub fn add_connection(&mut self, user_id: &Uuid, connection: &Connection) {
-        self.connections.insert(user_id.clone(), connection.clone());
+    pub fn add_connection(&mut self, connection: &Connection) {
+        self.connections
+            .insert(connection.get_session_id(), connection.clone());
+    }
+
+    /// Remove a connection by the session id from the connections list.
+    pub fn remove_connection(&mut self, session_id: &Uuid) {
+        self.connections.remove(session_id);
     }
 
-    /// Remove a connection by the user id from the connections list.
-    pub fn remove_connection(&mut self, user_id: &Uuid) {
-        self.connections.remove(user_id);
+    /// Get a connection by the session id.
+    pub fn get_connection(&self, session_id: &Uuid) -> Option<Connection> {
+        self.connections.get(session_id).cloned()
+    }
+
+    /// Get all connections.
+    pub fn get_connections(&self) -> Vec<Connection> {
+        self.connections.values().cloned().collect()
+    }
+
+    /// Get all connections of a user.
+    pub fn get_user_connections(&self, user_id: &Uuid) -> Vec<Connection> {
+        self.connections
+            .values()
+            .filter(|connection| &connection.get_user_id() == user_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the users with a live connection in any session.
+    pub fn get_connected_users(&self) -> HashSet<Uuid> {
+        self.connections
+            .values()
+            .map(|connection| connection.get_user_id())
+            .collect()
+    }
+
+    /// Check if a session has a live connection.
+    pub fn is_connected(&self, session_id: &Uuid) -> bool {
+        self.connections.contains_key(session_id)
+    }
+
+    /// Send the session closed packet to the connection of a session, close it and
+    /// remove it from the connections list.
+    pub fn close_connection(&mut self, session_id: &Uuid) {
+        if let Some(connection) = self.connections.remove(session_id) {
+            connection
+                .send_packet(Box::new(SessionClosedComposer::new(session_id.to_string())))
+                .ok();
+            connection.close().ok();
+        }
+    }
+
+    /// Add a device waiting to be linked by its pairing token until the lifetime ends.
+    ///
+    /// Returns `false` when there are too many pending links in total or from the IP address.
+    pub fn add_pending_link(
+        &mut self,
+        pairing_token: &str,
+        ip: &str,
+        lifetime: Duration,
+        sender: LinkSender,
+    ) -> bool {
+        if self.pending_links.len() >= MAX_PENDING_LINKS {
+            self.purge_pending_links();
+        }
+
+        let ip_links: usize = self.pending_links_by_ip.get(ip).copied().unwrap_or(0);
+        if self.pending_links.len() >= MAX_PENDING_LINKS || ip_links >= MAX_PENDING_LINKS_PER_IP {
+            return false;
+        }
+
+        self.pending_links_by_ip
+            .insert(String::from(ip), ip_links + 1);
+        self.pending_links.insert(
+            String::from(pairing_token),
+            PendingLink {
+                sender,
+                ip: String::from(ip),
+                expires_at: Instant::now() + lifetime,
+            },
+        );
+
+        true
+    }
+
+    /// Remove a pending link and get its sender, so it can only be confirmed once.
+    ///
+    /// The expired pending links are removed without returning their sender.
+    pub fn take_pending_link(&mut self, pairing_token: &str) -> Option<LinkSender> {
+        let pending_link = self.pending_links.remove(pairing_token)?;
+        self.remove_pending_link_ip(&pending_link.ip);
+
+        if pending_link.expires_at > Instant::now() {
+            Some(pending_link.sender)
+        } else {
+            None
+        }
+    }
+
+    /// Remove the expired pending links.
+    pub fn purge_pending_links(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .pending_links
+            .iter()
+            .filter(|(_, pending_link)| pending_link.expires_at <= now)
+            .map(|(pairing_token, _)| pairing_token.clone())
+            .collect();
+
+        for pairing_token in expired {
+            self.take_pending_link(&pairing_token);
+        }
+    }
+
+    /// Decrease the pending links count of an IP address.
+    fn remove_pending_link_ip(&mut self, ip: &str) {
+        if let Some(count) = self.pending_links_by_ip.get_mut(ip) {
+            *count -= 1;
+
+            if *count == 0 {
+                self.pending_links_by_ip.remove(ip);
+            }
+        }
     }
 
     /// Get the app versions object.
     pub fn get_versions(&self) -> AppVersions {
         self.versions.clone()
     }
+
+    /// Get the failed signin attempts by account.
+    pub fn get_account_attempts(&mut self) -> &mut LoginAttempts {
+        &mut self.account_attempts
+    }
+
+    /// Get the failed signin attempts by IP address.
+    pub fn get_ip_attempts(&mut self) -> &mut LoginAttempts {
+        &mut self.ip_attempts
+    }
+
+    /// Register the handler of the `friend_request` event.
+    pub fn set_friend_request_handler(&mut self, handler: FriendRequestHandlerType) {
+        self.friend_request_handler = Some(handler);
+    }
+
+    /// Get the handler of the `friend_request` event, if it was registered.
+    pub fn get_friend_request_handler(&self) -> Option<FriendRequestHandlerType> {
+        self.friend_request_handler.clone()
+    }
+
+    /// Forget the accounts and IP addresses without recent failed signin attempts.
+    pub fn purge_attempts(&mut self) {
+        self.account_attempts.purge();
+        self.ip_attempts.purge();
+    }
 }
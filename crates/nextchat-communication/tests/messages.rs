@@ -33,3 +33,151 @@ fn test_message_parse_from_string_error() {
 
     assert!(false);
 }
+
+#[test]
+fn test_session_closed_packet() {
+    use nextchat_communication::{PacketComposer, SessionClosedComposer};
+
+    let packet = SessionClosedComposer::new(String::from("5959ad9c-598e-4deb-bcbe-053c1f73b400"));
+    let message = packet.to_message();
+
+    assert_eq!(message.get_name(), String::from("session_closed"));
+    assert_eq!(
+        message.to_string(),
+        String::from("/session_closed 5959ad9c-598e-4deb-bcbe-053c1f73b400")
+    );
+}
+
+#[test]
+fn test_link_packets() {
+    use nextchat_communication::{
+        LinkConfirmedComposer, LinkSessionComposer, LinkTokenComposer, PacketComposer,
+    };
+
+    let message = LinkTokenComposer::new(String::from("Xk3bO0e7Lw2a"), 120).to_message();
+    assert_eq!(
+        message.to_string(),
+        String::from("/link_token Xk3bO0e7Lw2a 120")
+    );
+
+    let message = LinkSessionComposer::new(
+        String::from("5959ad9c-598e-4deb-bcbe-053c1f73b400"),
+        String::from("eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9"),
+        String::from("9fJ0pV2kqHc1"),
+    )
+    .to_message();
+    assert_eq!(message.get_name(), String::from("link_session"));
+    assert_eq!(message.get_arguments().len(), 3);
+
+    let message = LinkConfirmedComposer::new(false).to_message();
+    assert_eq!(message.to_string(), String::from("/link_confirmed false"));
+}
+
+#[tokio::test]
+async fn test_pending_link() {
+    use std::time::Duration;
+
+    use nextchat_communication::Storage;
+    use nextchat_database::Uuid;
+    use tokio::sync::oneshot;
+
+    let storage = Storage::default();
+    let (sender, receiver) = oneshot::channel();
+    let lifetime = Duration::from_secs(120);
+
+    assert!(storage
+        .write()
+        .await
+        .add_pending_link("Xk3bO0e7Lw2a", "127.0.0.1", lifetime, sender));
+
+    let user_id = Uuid::new_v4();
+    let sender = storage.write().await.take_pending_link("Xk3bO0e7Lw2a");
+    assert!(sender.is_some());
+    assert!(sender.unwrap().send(user_id).is_ok());
+    assert_eq!(receiver.await, Ok(user_id));
+
+    // The pairing token can only be used once.
+    assert!(storage
+        .write()
+        .await
+        .take_pending_link("Xk3bO0e7Lw2a")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_pending_link_limits() {
+    use std::time::Duration;
+
+    use nextchat_communication::Storage;
+    use tokio::sync::oneshot;
+
+    let storage = Storage::default();
+    let mut storage = storage.write().await;
+    let lifetime = Duration::from_secs(120);
+
+    for i in 0..5 {
+        let (sender, _) = oneshot::channel();
+        assert!(storage.add_pending_link(&format!("token{}", i), "127.0.0.1", lifetime, sender));
+    }
+
+    // Too many pending links from the same IP address.
+    let (sender, _) = oneshot::channel();
+    assert!(!storage.add_pending_link("token5", "127.0.0.1", lifetime, sender));
+
+    let (sender, _) = oneshot::channel();
+    assert!(storage.add_pending_link("token6", "127.0.0.2", lifetime, sender));
+
+    // A slot is released when a pending link is removed.
+    storage.take_pending_link("token0");
+    let (sender, _) = oneshot::channel();
+    assert!(storage.add_pending_link("token5", "127.0.0.1", lifetime, sender));
+
+    // The expired pending links cannot be confirmed.
+    let (sender, _) = oneshot::channel();
+    assert!(storage.add_pending_link("expired", "127.0.0.3", Duration::from_secs(0), sender));
+    assert!(storage.take_pending_link("expired").is_none());
+}
+
+#[test]
+fn test_announcement_packet() {
+    use nextchat_communication::{AnnouncementComposer, PacketComposer};
+
+    let message =
+        AnnouncementComposer::new(String::from("The server will restart soon.")).to_message();
+    assert_eq!(message.get_name(), String::from("announcement"));
+    assert_eq!(
+        message.to_string(),
+        String::from("/announcement The server will restart soon.")
+    );
+}
+
+#[test]
+fn test_friend_request_packets() {
+    use nextchat_communication::{
+        FriendRequestReceivedComposer, FriendRequestResultComposer, PacketComposer,
+    };
+
+    let user_id = String::from("5959ad9c-598e-4deb-bcbe-053c1f73b400");
+
+    let message =
+        FriendRequestReceivedComposer::new(user_id.clone(), String::from("NextChat")).to_message();
+    assert_eq!(
+        message.to_string(),
+        format!("/friend_request_received {} NextChat", user_id)
+    );
+
+    let message = FriendRequestResultComposer::new(user_id.clone(), None).to_message();
+    assert_eq!(
+        message.to_string(),
+        format!("/friend_request_result {} true", user_id)
+    );
+
+    let message =
+        FriendRequestResultComposer::new(user_id.clone(), Some(String::from("already_friends")))
+            .to_message();
+    assert_eq!(message.get_arguments()[1], String::from("false"));
+    assert_eq!(
+        message.to_string(),
+        format!("/friend_request_result {} false already_friends", user_id)
+    );
+}
@@ -5,11 +5,28 @@ pub mod models;
 use std::env;
 
 pub use chrono::NaiveDateTime;
-pub use sqlx::{postgres::PgRow, query, Error, Row, Type};
+pub use sqlx::{postgres::PgRow, query, Error, Postgres, Row, Transaction, Type};
 pub use uuid::Uuid;
 
 pub type Client = sqlx::PgPool;
 
+/// Escape the `\`, `%` and `_` characters of a text used in a `LIKE` pattern
+/// with `ESCAPE '\'`, so they are matched literally.
+///
+/// # Example
+/// ```rust
+/// use nextchat_database::escape_like;
+///
+/// fn main() {
+///     assert_eq!(escape_like("next_chat%"), "next\\_chat\\%");
+/// }
+/// ```
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 /// Generate a database pool connection to PostgreSQL.
 ///
 /// # Example
@@ -1,4 +1,14 @@
 //! NextChat Database models module.
 
+pub mod admin;
+pub mod audit;
+pub mod blocks;
+pub mod email;
+pub mod export;
 pub mod friends;
+pub mod invites;
+pub mod passkeys;
+pub mod roles;
+pub mod sessions;
+pub mod two_factor;
 pub mod users;
@@ -0,0 +1,103 @@
+//! NextChat Database admin models module.
+//!
+//! This module contains the structs for the admin routes.
+//!
+//! `/admin/users`              query -> AdminUsersQuery
+//! `/admin/users/:user_id/ban` body  -> BanBody
+//! `/admin/announcements`      body  -> AnnouncementBody
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+use super::roles::Role;
+
+#[derive(Deserialize)]
+pub struct AdminUsersQuery {
+    pub skip: Option<i64>,
+    pub take: Option<i64>,
+    pub search: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BanBody {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AnnouncementBody {
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct AdminUserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_verified_at: Option<NaiveDateTime>,
+    pub profile_image: String,
+    pub role: Role,
+    pub totp_enabled: bool,
+    pub logged: bool,
+    pub last_online: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub invited_by: Option<Uuid>,
+    pub banned_at: Option<NaiveDateTime>,
+    pub ban_reason: Option<String>,
+    pub deletion_scheduled_at: Option<NaiveDateTime>,
+}
+
+impl AdminUserResponse {
+    /// Parse a SQLx row to an AdminUserResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.try_get("id").expect("Cannot parse the user id."),
+            username: row.try_get("username").expect("Cannot parse the username."),
+            email: row.try_get("email").expect("Cannot parse the user email."),
+            email_verified_at: row
+                .try_get("email_verified_at")
+                .expect("Cannot parse the user email verified at timestamp."),
+            profile_image: row
+                .try_get("profile_image")
+                .expect("Cannot parse the user profile image."),
+            role: row.try_get("role").expect("Cannot parse the user role."),
+            totp_enabled: row
+                .try_get("totp_enabled")
+                .expect("Cannot parse the user TOTP state."),
+            logged: row
+                .try_get("logged")
+                .expect("Cannot parse the user logged state."),
+            last_online: row
+                .try_get("last_online")
+                .expect("Cannot parse the user last online timestamp."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the user created at timestamp."),
+            invited_by: row
+                .try_get("invited_by")
+                .expect("Cannot parse the user inviter id."),
+            banned_at: row
+                .try_get("banned_at")
+                .expect("Cannot parse the user banned at timestamp."),
+            ban_reason: row
+                .try_get("ban_reason")
+                .expect("Cannot parse the user ban reason."),
+            deletion_scheduled_at: row
+                .try_get("deletion_scheduled_at")
+                .expect("Cannot parse the user deletion timestamp."),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ConnectionResponse {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub connected_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct AnnouncementResponse {
+    pub recipients: usize,
+}
@@ -0,0 +1,72 @@
+//! NextChat Database audit models module.
+//!
+//! This module contains the AuditEvent enum type of the audit log and the structs
+//! for the admin audit log route.
+//!
+//! `/admin/audit-log` query -> AuditLogQuery
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, types::JsonValue, Row};
+use uuid::Uuid;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[sqlx(type_name = "audit_event", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Signup,
+    Signin,
+    SigninFailed,
+    PasswordChange,
+    RoleChange,
+    Ban,
+    Unban,
+    AccountDeletionScheduled,
+    AccountDeleted,
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    pub user_id: Option<Uuid>,
+    pub event: Option<AuditEvent>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub skip: Option<i64>,
+    pub take: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogResponse {
+    pub id: i64,
+    pub event: AuditEvent,
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub metadata: JsonValue,
+    pub created_at: NaiveDateTime,
+}
+
+impl AuditLogResponse {
+    /// Parse a SQLx row to an AuditLogResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.try_get("id").expect("Cannot parse the audit log id."),
+            event: row
+                .try_get("event")
+                .expect("Cannot parse the audit log event."),
+            actor_id: row
+                .try_get("actor_id")
+                .expect("Cannot parse the audit log actor id."),
+            target_id: row
+                .try_get("target_id")
+                .expect("Cannot parse the audit log target id."),
+            ip: row.try_get("ip").expect("Cannot parse the audit log ip."),
+            metadata: row
+                .try_get("metadata")
+                .expect("Cannot parse the audit log metadata."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the audit log created at timestamp."),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! NextChat Database blocks models module.
+//!
+//! This module contains the structs for the blocks routes.
+//!
+//! `/users/blocks` query -> BlocksQuery
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+
+use super::users::UserDataResponse;
+
+#[derive(Deserialize)]
+pub struct BlocksQuery {
+    pub skip: Option<i64>,
+    pub take: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct BlockResponse {
+    pub user: UserDataResponse,
+    pub blocked_at: NaiveDateTime,
+}
+
+impl BlockResponse {
+    /// Parse a SQLx row to a BlockResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            user: UserDataResponse::from_row(row),
+            blocked_at: row
+                .try_get("blocked_at")
+                .expect("Cannot parse the block created at timestamp."),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! NextChat Database email models module.
+//!
+//! This module contains the structs for the email verification routes.
+//!
+//! `/users/email`          body -> ChangeEmailBody
+//! `/users/email/verify`   body -> VerifyEmailBody
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ChangeEmailBody {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailBody {
+    pub token: String,
+}
+
+#[derive(Serialize)]
+pub struct EmailResponse {
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
@@ -0,0 +1,53 @@
+//! NextChat Database export models module.
+//!
+//! This module contains the structs of the personal data export.
+//!
+//! `/users/export` response -> ExportResponse
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{
+    audit::AuditLogResponse, blocks::BlockResponse, friends::FriendState, invites::InviteResponse,
+    passkeys::PasskeyResponse, roles::Role, sessions::SessionResponse,
+};
+
+#[derive(Serialize)]
+pub struct ExportProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_verified_at: Option<NaiveDateTime>,
+    pub profile_image: String,
+    pub totp_enabled: bool,
+    pub role: Role,
+    pub invited_by: Option<Uuid>,
+    pub banned_at: Option<NaiveDateTime>,
+    pub ban_reason: Option<String>,
+    pub last_online: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub deletion_scheduled_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct ExportFriendship {
+    pub user_id: Uuid,
+    pub username: String,
+    pub state: FriendState,
+    pub sent_by_me: bool,
+    pub since: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct ExportResponse {
+    pub exported_at: NaiveDateTime,
+    pub profile: ExportProfile,
+    pub friendships: Vec<ExportFriendship>,
+    pub blocked_users: Vec<BlockResponse>,
+    pub sessions: Vec<SessionResponse>,
+    pub passkeys: Vec<PasskeyResponse>,
+    pub recovery_codes_remaining: i64,
+    pub invites: Vec<InviteResponse>,
+    pub audit_log: Vec<AuditLogResponse>,
+}
@@ -1,14 +1,23 @@
 //! NextChat Database friends models module.
 //!
 //! This module contains the FriendState enum type and the FriendModel
-//! structure for database queries.
+//! structure for database queries, and the structs for the friends routes.
+//!
+//! `/friends/get-of/:user_id`      query -> FriendsQuery
+//! `/friends/requests/incoming`    query -> FriendsQuery
+//! `/friends/requests/outgoing`    query -> FriendsQuery
+//! `/friends/suggestions`          query -> FriendsQuery
 
 use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgRow, Row};
 use uuid::Uuid;
 
-#[derive(sqlx::Type, Clone)]
+use super::users::UserDataResponse;
+
+#[derive(sqlx::Type, Serialize, Clone)]
 #[sqlx(type_name = "friends_state", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum FriendState {
     Requested,
     Approved,
@@ -59,6 +68,16 @@ impl FriendModel {
         }
     }
 
+    /// Get the id of the user that sent the friend request.
+    pub fn get_transmitter(&self) -> Uuid {
+        self.transmitter
+    }
+
+    /// Get the id of the user that received the friend request.
+    pub fn get_receiver(&self) -> Uuid {
+        self.receiver
+    }
+
     /// Get the friend state (Approved or requested).
     pub fn get_state(&self) -> FriendState {
         self.state.clone()
@@ -69,3 +88,82 @@ impl FriendModel {
         self.since
     }
 }
+
+#[derive(Serialize)]
+pub struct FriendshipResponse {
+    pub user_id: Uuid,
+    pub state: FriendState,
+    pub sent_by_me: bool,
+    pub since: NaiveDateTime,
+}
+
+impl FriendshipResponse {
+    /// Get the friendship of a FriendModel from the side of a user.
+    pub fn from_model(friend: &FriendModel, user_id: &Uuid) -> Self {
+        let sent_by_me: bool = friend.get_transmitter() == *user_id;
+
+        Self {
+            user_id: if sent_by_me {
+                friend.get_receiver()
+            } else {
+                friend.get_transmitter()
+            },
+            state: friend.get_state(),
+            sent_by_me,
+            since: friend.get_since(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FriendsQuery {
+    pub skip: Option<i64>,
+    pub take: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct FriendResponse {
+    pub user: UserDataResponse,
+    pub since: NaiveDateTime,
+    pub connected: bool,
+}
+
+#[derive(Serialize)]
+pub struct FriendRequestResponse {
+    pub user: UserDataResponse,
+    pub since: NaiveDateTime,
+}
+
+impl FriendRequestResponse {
+    /// Parse a SQLx row to a FriendRequestResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            user: UserDataResponse::from_row(row),
+            since: row
+                .try_get("since")
+                .expect("Cannot parse the friend since timestamp."),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct FriendSuggestionResponse {
+    pub user: UserDataResponse,
+    pub mutual_friends: i64,
+    pub mutual_friend_names: Vec<String>,
+}
+
+impl FriendSuggestionResponse {
+    /// Parse a SQLx row to a FriendSuggestionResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            user: UserDataResponse::from_row(row),
+            mutual_friends: row
+                .try_get("mutual_friends")
+                .expect("Cannot parse the mutual friends count."),
+            mutual_friend_names: row
+                .try_get("mutual_friend_names")
+                .expect("Cannot parse the mutual friend names."),
+        }
+    }
+}
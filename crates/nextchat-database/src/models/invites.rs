@@ -0,0 +1,71 @@
+//! NextChat Database invites models module.
+//!
+//! This module contains the structs for the invites routes.
+//!
+//! `/users/invites` body -> CreateInviteBody
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct CreateInviteBody {
+    pub max_uses: Option<i32>,
+    pub expires_in_days: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct InviteResponse {
+    pub code: String,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl InviteResponse {
+    /// Parse a SQLx row to an InviteResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            code: row.try_get("code").expect("Cannot parse the invite code."),
+            max_uses: row
+                .try_get("max_uses")
+                .expect("Cannot parse the invite max uses."),
+            uses: row.try_get("uses").expect("Cannot parse the invite uses."),
+            expires_at: row
+                .try_get("expires_at")
+                .expect("Cannot parse the invite expires at timestamp."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the invite created at timestamp."),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct InvitedUserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl InvitedUserResponse {
+    /// Parse a SQLx row to an InvitedUserResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.try_get("id").expect("Cannot parse the user id."),
+            username: row.try_get("username").expect("Cannot parse the username."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the user created at timestamp."),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct InvitesResponse {
+    pub invites: Vec<InviteResponse>,
+    pub invited_users: Vec<InvitedUserResponse>,
+    pub remaining_uses: Option<i64>,
+}
@@ -0,0 +1,63 @@
+//! NextChat Database passkeys models module.
+//!
+//! This module contains the structs for the passkeys routes. The WebAuthn
+//! credentials are parsed by the server using the `nextchat-security` types.
+//!
+//! `/users/passkeys/register/start`    body -> users::PasswordConfirmationBody
+//! `/users/passkeys/register/finish`   body -> FinishPasskeyRegistrationBody
+//! `/users/signin/passkey/start`       body -> StartPasskeySignInBody
+//! `/users/signin/passkey/finish`      body -> FinishPasskeySignInBody
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, types::JsonValue, Row};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct FinishPasskeyRegistrationBody {
+    pub ceremony_id: String,
+    pub name: Option<String>,
+    pub credential: JsonValue,
+}
+
+#[derive(Deserialize)]
+pub struct StartPasskeySignInBody {
+    pub username: String,
+}
+
+#[derive(Deserialize)]
+pub struct FinishPasskeySignInBody {
+    pub ceremony_id: String,
+    pub credential: JsonValue,
+    pub device_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PasskeyChallengeResponse {
+    pub ceremony_id: String,
+    pub options: JsonValue,
+}
+
+#[derive(Serialize)]
+pub struct PasskeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub last_used: NaiveDateTime,
+}
+
+impl PasskeyResponse {
+    /// Parse a SQLx row to a PasskeyResponse.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.try_get("id").expect("Cannot parse the passkey id."),
+            name: row.try_get("name").expect("Cannot parse the passkey name."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the passkey created at timestamp."),
+            last_used: row
+                .try_get("last_used")
+                .expect("Cannot parse the passkey last used timestamp."),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! NextChat Database roles models module.
+//!
+//! This module contains the Role enum type of the users, the permissions of
+//! each role and the structs for the admin roles routes.
+//!
+//! `/admin/users/:user_id/role` body -> ChangeRoleBody
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    /// View the private data of the users.
+    ViewUsers,
+    /// Manage the accounts of the users.
+    ManageUsers,
+    /// Grant and revoke the roles of the users.
+    ManageRoles,
+    /// Send system announcements to the connections.
+    SendAnnouncements,
+    /// View the audit log of the security events.
+    ViewAuditLog,
+    /// Create invites without the quota of the users.
+    ManageInvites,
+}
+
+impl Role {
+    /// Get the permissions of the role.
+    pub fn get_permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::User => &[],
+            Role::Moderator => &[Permission::ViewUsers],
+            Role::Admin => &[
+                Permission::ViewUsers,
+                Permission::ManageUsers,
+                Permission::ManageRoles,
+                Permission::SendAnnouncements,
+                Permission::ViewAuditLog,
+                Permission::ManageInvites,
+            ],
+        }
+    }
+
+    /// Check if the role has a permission.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nextchat_database::models::roles::{Permission, Role};
+    ///
+    /// fn main() {
+    ///     assert!(Role::Admin.has_permission(Permission::ManageRoles));
+    ///     assert!(!Role::Moderator.has_permission(Permission::ManageRoles));
+    ///     assert!(!Role::User.has_permission(Permission::ViewUsers));
+    /// }
+    /// ```
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.get_permissions().contains(&permission)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChangeRoleBody {
+    pub role: Role,
+}
+
+#[derive(Serialize)]
+pub struct UserRoleResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+}
@@ -0,0 +1,96 @@
+//! NextChat Database sessions models module.
+//!
+//! This module contains the SessionModel structure for database queries and
+//! the structs for the authentication routes.
+//!
+//! `/auth/refresh` body -> RefreshBody
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct RefreshBody {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize)]
+pub struct SessionTokensResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Clone)]
+pub struct SessionModel {
+    id: Uuid,
+    device_name: String,
+    user_agent: String,
+    ip: String,
+    created_at: NaiveDateTime,
+    last_used: NaiveDateTime,
+}
+
+impl SessionModel {
+    /// Parse a SQLx row to a SessionModel struct.
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.try_get("id").expect("Cannot parse the session id."),
+            device_name: row
+                .try_get("device_name")
+                .expect("Cannot parse the session device name."),
+            user_agent: row
+                .try_get("user_agent")
+                .expect("Cannot parse the session user agent."),
+            ip: row.try_get("ip").expect("Cannot parse the session ip."),
+            created_at: row
+                .try_get("created_at")
+                .expect("Cannot parse the session created at timestamp."),
+            last_used: row
+                .try_get("last_used")
+                .expect("Cannot parse the session last used timestamp."),
+        }
+    }
+
+    /// Get the session id.
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Get the session device name.
+    pub fn get_device_name(&self) -> String {
+        self.device_name.clone()
+    }
+
+    /// Get the session user agent.
+    pub fn get_user_agent(&self) -> String {
+        self.user_agent.clone()
+    }
+
+    /// Get the IP address that created the session.
+    pub fn get_ip(&self) -> String {
+        self.ip.clone()
+    }
+
+    /// Get the created at timestamp.
+    pub fn get_created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    /// Get the last used timestamp.
+    pub fn get_last_used(&self) -> NaiveDateTime {
+        self.last_used
+    }
+}
+
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device_name: String,
+    pub user_agent: String,
+    pub ip: String,
+    pub created_at: NaiveDateTime,
+    pub last_used: NaiveDateTime,
+    pub connected: bool,
+    pub current: bool,
+}
@@ -0,0 +1,40 @@
+//! NextChat Database two-factor authentication models module.
+//!
+//! This module contains the structs for the two-factor authentication routes.
+//!
+//! `/users/2fa/enroll`     body -> users::PasswordConfirmationBody
+//! `/users/2fa/confirm`    body -> TotpCodeBody
+//! `/users/2fa/disable`    body -> DisableTotpBody
+//! `/users/signin/2fa`     body -> SecondFactorBody
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct TotpCodeBody {
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct DisableTotpBody {
+    pub password: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct SecondFactorBody {
+    pub challenge: String,
+    pub code: String,
+    pub device_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub uri: String,
+}
+
+#[derive(Serialize)]
+pub struct SecondFactorChallengeResponse {
+    pub second_factor_required: bool,
+    pub challenge: String,
+}
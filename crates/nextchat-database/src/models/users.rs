@@ -2,16 +2,23 @@
 //!
 //! This module contains the structs for the users routes.
 //!
-//! `/users/all`    query -> GetAllQuery
-//! `/users/search` query -> SearchQuery
-//! `/users/find`   query -> FindQuery
-//! `/users/signup` body  -> SignUpAndSignInBody
-//! `/users/signin` body  -> SignUpAndSignInBody
+//! `/users/all`            query -> GetAllQuery
+//! `/users/search`         query -> SearchQuery
+//! `/users/find`           query -> FindQuery
+//! `/users/signup`         body  -> SignUpAndSignInBody
+//! `/users/signin`         body  -> SignUpAndSignInBody
+//! `/users/password`       body  -> ChangePasswordBody
+//! `/users/recover`        body  -> RecoverBody
+//! `/users/recovery-codes` body  -> PasswordConfirmationBody
+//! `/users/account`        body  -> PasswordConfirmationBody
 
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgRow, Row};
 use uuid::Uuid;
 
+use super::sessions::SessionTokensResponse;
+
 #[derive(Deserialize)]
 pub struct GetAllQuery {
     pub skip: Option<i64>,
@@ -34,6 +41,35 @@ pub struct FindQuery {
 pub struct SignUpAndSignInBody {
     pub username: String,
     pub password: String,
+    pub device_name: Option<String>,
+    pub invite_code: Option<String>,
+    pub pow_challenge: Option<String>,
+    pub pow_solution: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SignUpChallengeResponse {
+    pub challenge: String,
+    pub difficulty: u8,
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordBody {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct RecoverBody {
+    pub username: String,
+    pub recovery_code: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct PasswordConfirmationBody {
+    pub password: String,
 }
 
 #[derive(Serialize)]
@@ -55,3 +91,22 @@ impl UserDataResponse {
         }
     }
 }
+
+#[derive(Serialize)]
+pub struct AuthenticationResponse {
+    pub user: UserDataResponse,
+    #[serde(flatten)]
+    pub tokens: SessionTokensResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct AccountDeletionResponse {
+    pub deletion_scheduled_at: NaiveDateTime,
+}
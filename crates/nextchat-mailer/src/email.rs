@@ -0,0 +1,71 @@
+//! NextChat Mailer email module.
+
+/// Max length of the email addresses.
+const EMAIL_MAX_LENGTH: usize = 254;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Email {
+    /// Create a new plain text email.
+    pub fn new(to: &str, subject: &str, body: &str) -> Self {
+        Self {
+            to: String::from(to),
+            subject: String::from(subject),
+            body: String::from(body),
+        }
+    }
+}
+
+/// Check the format of an email address and get it without the surrounding
+/// whitespaces and with the domain in lowercase.
+///
+/// # Example
+/// ```rust
+/// use nextchat_mailer::validate_email;
+///
+/// fn main() {
+///     assert_eq!(
+///         validate_email(" Daniel@NextChat.org "),
+///         Ok(String::from("Daniel@nextchat.org"))
+///     );
+///     assert!(validate_email("daniel@localhost").is_err());
+///     assert!(validate_email("daniel nextchat.org").is_err());
+/// }
+/// ```
+pub fn validate_email(email: &str) -> Result<String, &'static str> {
+    let email: &str = email.trim();
+
+    if email.is_empty() {
+        return Err("You must enter the email.");
+    } else if email.len() > EMAIL_MAX_LENGTH {
+        return Err("The email is too long.");
+    }
+
+    let (local, domain) = match email.rfind('@') {
+        Some(index) => (&email[..index], &email[index + 1..]),
+        None => return Err("The email is invalid."),
+    };
+
+    let is_valid_domain: bool = domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+
+    if local.is_empty()
+        || local.contains('@')
+        || local.chars().any(|c| c.is_whitespace() || c.is_control())
+        || !is_valid_domain
+    {
+        return Err("The email is invalid.");
+    }
+
+    Ok(format!("{}@{}", local, domain.to_lowercase()))
+}
@@ -0,0 +1,57 @@
+//! NextChat Mailer file module.
+//!
+//! This module contains the mailer that writes the emails in a directory, one
+//! file for each email, instead of sending them.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+
+use super::{Email, Mailer};
+
+pub struct FileMailer {
+    directory: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    /// Create a new mailer that writes the emails in the directory.
+    pub fn new(directory: &str, from: &str) -> Self {
+        Self {
+            directory: PathBuf::from(directory),
+            from: String::from(from),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: &Email) -> Result<(), String> {
+        let timestamp: u128 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+
+        let path: PathBuf = self.directory.join(format!(
+            "{}-{}.eml",
+            timestamp,
+            email.to.replace(|c: char| !c.is_alphanumeric(), "_")
+        ));
+
+        let content: String = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+            self.from, email.to, email.subject, email.body
+        );
+
+        if tokio::fs::create_dir_all(&self.directory).await.is_err()
+            || tokio::fs::write(&path, content).await.is_err()
+        {
+            return Err(format!("Cannot write the email to {}.", path.display()));
+        }
+
+        Ok(())
+    }
+}
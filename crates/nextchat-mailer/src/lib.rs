@@ -0,0 +1,81 @@
+//! NextChat Mailer library.
+//!
+//! This module contains the `Mailer` trait used to send the emails of the app and
+//! its backends: `SmtpMailer` sends the emails with a SMTP server and `FileMailer`
+//! writes them in a directory, for development and tests.
+//!
+//! # Environment variables
+//! - `MAILER` _Default_ `file` - The backend: `smtp` or `file`.
+//! - `MAILER_FROM` _Default_ `NextChat <no-reply@localhost>` - The sender of the emails.
+//! - `MAILER_DIR` _Default_ `mails` - The directory of the `file` backend.
+//! - `SMTP_HOST` **Required with `smtp`** - The host of the SMTP server (STARTTLS).
+//! - `SMTP_PORT` _Default_ `587` - The port of the SMTP server.
+//! - `SMTP_USERNAME` and `SMTP_PASSWORD` _Optional_ - The SMTP credentials.
+
+mod email;
+mod file;
+mod smtp;
+
+use std::{env, sync::Arc};
+
+use async_trait::async_trait;
+
+pub use email::{validate_email, Email};
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+pub type MailerType = Arc<dyn Mailer>;
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Send an email, get the reason when it cannot be sent.
+    async fn send(&self, email: &Email) -> Result<(), String>;
+}
+
+/// Get the sender of the emails from the `MAILER_FROM` environment variable.
+fn get_sender() -> String {
+    env::var("MAILER_FROM").unwrap_or_else(|_| String::from("NextChat <no-reply@localhost>"))
+}
+
+/// Get the mailer backend from the environment variables.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_mailer::{from_env, MailerType};
+///
+/// fn main() {
+///     env::set_var("MAILER", "file");
+///
+///     let _mailer: MailerType = from_env();
+/// }
+/// ```
+pub fn from_env() -> MailerType {
+    match env::var("MAILER").unwrap_or_default().as_str() {
+        "smtp" => {
+            let host: String = match env::var("SMTP_HOST") {
+                Ok(host) => host,
+                Err(_) => {
+                    panic!("Mailer Error -> Cannot get the `SMTP_HOST` variable.");
+                }
+            };
+
+            let port: u16 = env::var("SMTP_PORT")
+                .ok()
+                .and_then(|port| port.parse::<u16>().ok())
+                .unwrap_or(587);
+
+            let credentials = match (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD")) {
+                (Ok(username), Ok(password)) if !username.is_empty() => Some((username, password)),
+                _ => None,
+            };
+
+            Arc::new(SmtpMailer::new(&host, port, credentials, &get_sender()))
+        }
+        _ => Arc::new(FileMailer::new(
+            &env::var("MAILER_DIR").unwrap_or_else(|_| String::from("mails")),
+            &get_sender(),
+        )),
+    }
+}
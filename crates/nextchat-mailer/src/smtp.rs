@@ -0,0 +1,74 @@
+//! NextChat Mailer SMTP module.
+//!
+//! This module contains the mailer that sends the emails with a SMTP server
+//! using STARTTLS.
+
+use async_trait::async_trait;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+use super::{Email, Mailer};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Create a new mailer for the SMTP server, the `credentials` are the username
+    /// and the password.
+    pub fn new(host: &str, port: u16, credentials: Option<(String, String)>, from: &str) -> Self {
+        let from: Mailbox = match from.parse() {
+            Ok(from) => from,
+            Err(_) => {
+                panic!("Mailer Error -> `{}` is not a valid sender.", from);
+            }
+        };
+
+        let mut transport = match AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host) {
+            Ok(transport) => transport.port(port),
+            Err(_) => {
+                panic!(
+                    "Mailer Error -> Cannot connect to the SMTP server `{}`.",
+                    host
+                );
+            }
+        };
+
+        if let Some((username, password)) = credentials {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+
+        Self {
+            transport: transport.build(),
+            from,
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), String> {
+        let to: Mailbox = match email.to.parse() {
+            Ok(to) => to,
+            Err(_) => return Err(format!("`{}` is not a valid email.", email.to)),
+        };
+
+        let message: Message = match Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.clone())
+            .body(email.body.clone())
+        {
+            Ok(message) => message,
+            Err(_) => return Err(String::from("Cannot build the email.")),
+        };
+
+        match self.transport.send(message).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Cannot send the email: {}", e)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::env;
+
+use nextchat_mailer::{validate_email, Email, FileMailer, Mailer};
+
+#[test]
+fn test_email_validation() {
+    assert_eq!(
+        validate_email("contact@NextChat.ORG"),
+        Ok(String::from("contact@nextchat.org"))
+    );
+    assert_eq!(
+        validate_email("daniel+test@mail.nextchat.org"),
+        Ok(String::from("daniel+test@mail.nextchat.org"))
+    );
+
+    assert_eq!(validate_email(""), Err("You must enter the email."));
+    assert!(validate_email("nextchat.org").is_err());
+    assert!(validate_email("@nextchat.org").is_err());
+    assert!(validate_email("contact@").is_err());
+    assert!(validate_email("contact@nextchat..org").is_err());
+    assert!(validate_email("contact@-nextchat.org").is_err());
+    assert!(validate_email(&format!("{}@nextchat.org", "a".repeat(250))).is_err());
+}
+
+#[tokio::test]
+async fn test_file_mailer() {
+    let directory = env::temp_dir().join("nextchat-mailer-test");
+    let _ = tokio::fs::remove_dir_all(&directory).await;
+
+    let mailer = FileMailer::new(
+        directory.to_str().unwrap(),
+        "NextChat <no-reply@nextchat.org>",
+    );
+    let email = Email::new("contact@nextchat.org", "Hello", "Welcome to NextChat.");
+    assert!(mailer.send(&email).await.is_ok());
+
+    let mut entries = tokio::fs::read_dir(&directory).await.unwrap();
+    let entry = entries.next_entry().await.unwrap().unwrap();
+    let content = tokio::fs::read_to_string(entry.path()).await.unwrap();
+
+    assert!(content.contains("To: contact@nextchat.org\r\n"));
+    assert!(content.contains("Subject: Hello\r\n"));
+    assert!(content.ends_with("Welcome to NextChat.\r\n"));
+    assert!(entries.next_entry().await.unwrap().is_none());
+}
@@ -0,0 +1,132 @@
+//! NextChat Security keyring module.
+//!
+//! This module contains the keyring with the current app secret key and the
+//! previous secret keys, so the secret key can be rotated without locking out
+//! the users.
+//!
+//! # Environment variables
+//! - `APP_SECRET_KEY` **Required** - The current secret key.
+//! - `APP_SECRET_KEY_ID` _Default_ `1` - The id of the current secret key.
+//! - `APP_PREVIOUS_SECRET_KEYS` _Optional_ - The previous secret keys: `{id}:{key},{id}:{key}`.
+
+use std::env;
+
+/// Default id of the current secret key.
+const DEFAULT_KEY_ID: &str = "1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecretKey {
+    id: String,
+    key: String,
+}
+
+impl SecretKey {
+    /// Create a new secret key.
+    pub fn new(id: &str, key: &str) -> Self {
+        // The ids are stored before the password hash and in the lists of keys.
+        if id.is_empty() || id.contains(|c| c == '$' || c == ':' || c == ',') {
+            panic!(
+                "Security Error -> `{}` is not a valid secret key id, it cannot be empty or contain `$`, `:` or `,`.",
+                id
+            );
+        }
+
+        Self {
+            id: String::from(id),
+            key: String::from(key),
+        }
+    }
+
+    /// Get the secret key id.
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the secret key.
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keyring {
+    current: SecretKey,
+    previous: Vec<SecretKey>,
+}
+
+impl Keyring {
+    /// Create a new keyring.
+    pub fn new(current: SecretKey, previous: Vec<SecretKey>) -> Self {
+        Self { current, previous }
+    }
+
+    /// Get the keyring from the environment variables.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::env;
+    ///
+    /// use nextchat_security::Keyring;
+    ///
+    /// fn main() {
+    ///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+    ///     env::set_var("APP_SECRET_KEY_ID", "2");
+    ///     env::set_var("APP_PREVIOUS_SECRET_KEYS", "1:OldNextChatORG");
+    ///
+    ///     let keyring = Keyring::from_env();
+    ///     assert_eq!(keyring.get_current().get_id(), "2");
+    ///     assert_eq!(keyring.get("1").unwrap().get_key(), "OldNextChatORG");
+    /// }
+    /// ```
+    pub fn from_env() -> Self {
+        let key: String = match env::var("APP_SECRET_KEY") {
+            Ok(secret_key) => secret_key,
+            Err(_) => {
+                panic!("Security Error -> Cannot get the `APP_SECRET_KEY` variable.");
+            }
+        };
+
+        let id: String =
+            env::var("APP_SECRET_KEY_ID").unwrap_or_else(|_| String::from(DEFAULT_KEY_ID));
+
+        let previous: Vec<SecretKey> = env::var("APP_PREVIOUS_SECRET_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                let mut pair = pair.trim().splitn(2, ':');
+
+                match (pair.next(), pair.next()) {
+                    (Some(id), Some(key)) if !key.is_empty() => SecretKey::new(id, key),
+                    _ => panic!(
+                        "Security Error -> The `APP_PREVIOUS_SECRET_KEYS` variable must be `{}`.",
+                        "{id}:{key},{id}:{key}"
+                    ),
+                }
+            })
+            .collect();
+
+        Self::new(SecretKey::new(&id, &key), previous)
+    }
+
+    /// Get the current secret key.
+    pub fn get_current(&self) -> &SecretKey {
+        &self.current
+    }
+
+    /// Get a secret key by its id.
+    pub fn get(&self, id: &str) -> Option<&SecretKey> {
+        if self.current.id == id {
+            return Some(&self.current);
+        }
+
+        self.previous.iter().find(|key| key.id == id)
+    }
+
+    /// Get all secret keys, starting with the current secret key.
+    pub fn get_all(&self) -> Vec<&SecretKey> {
+        let mut keys: Vec<&SecretKey> = [&self.current].to_vec();
+        keys.extend(self.previous.iter());
+        keys
+    }
+}
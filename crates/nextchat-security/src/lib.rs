@@ -4,22 +4,79 @@
 //! Argon2id algorithm and the app secret key. Also, contains the function
 //! to verify the user password using the posible password and the
 //! encrypted password hash.
+//!
+//! The password hashes are stored with the id of the secret key used to encrypt
+//! them: `{key_id}$argon2id$v=19$m=4096,t=192,p=4${salt}${hash}`. The hashes
+//! without key id were encrypted before the keyring and are verified with all keys.
+//!
+//! See `./keyring.rs` for the secret keys, `./parameters.rs` for the Argon2id
+//! cost parameters, `./password_policy.rs` for the password strength requirements,
+//! `./proof_of_work.rs` for the signup proof of work, `./tokens.rs` for the session
+//! tokens functions, `./totp.rs` for the two-factor authentication functions and
+//! `./passkeys.rs` for the WebAuthn ceremonies.
 
-use std::env;
+mod keyring;
+mod parameters;
+mod passkeys;
+mod password_policy;
+mod proof_of_work;
+mod tokens;
+mod totp;
 
 use argonautica::{Hasher, Verifier};
+use once_cell::sync::Lazy;
 
-/// Get the `APP_SECRET_KEY` variable from the environment.
-fn get_secret_key() -> String {
-    match env::var("APP_SECRET_KEY") {
-        Ok(secret_key) => secret_key,
-        Err(_) => {
-            panic!("Security Error -> Cannot get the `APP_SECRET_KEY` variable.");
-        }
+pub use keyring::{Keyring, SecretKey};
+pub use parameters::HashParameters;
+pub use passkeys::{
+    get_credential_id, CreationChallengeResponse, Credential, Passkeys, PasskeysConfig,
+    PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse,
+};
+pub use password_policy::{estimate_password_entropy, is_breached_password, PasswordPolicy};
+pub use proof_of_work::{solve_proof_of_work, verify_proof_of_work, ProofOfWork};
+pub use tokens::{
+    decode_token, encode_token, format_recovery_code, generate_random_token,
+    generate_recovery_code, get_timestamp, hash_token, normalize_recovery_code,
+};
+pub use totp::{
+    decrypt_secret, encrypt_secret, generate_totp_secret, get_totp_code, get_totp_uri,
+    verify_totp_code,
+};
+
+/// Hash of a random password used to verify the passwords of the users that do not exist.
+static DUMMY_PASSWORD_HASH: Lazy<String> = Lazy::new(|| {
+    encrypt_password(&generate_random_token()).expect("Cannot hash the dummy password.")
+});
+
+/// Split a stored password hash into the secret key id and the Argon2id hash.
+///
+/// The key id is `None` for the hashes encrypted before the keyring.
+fn split_password_hash(password_hash: &str) -> (Option<&str>, &str) {
+    match password_hash.find('$') {
+        Some(index) if index > 0 => (Some(&password_hash[..index]), &password_hash[index..]),
+        _ => (None, password_hash),
     }
 }
 
-/// Encrypt an user password using Argon2id algorithm and the app secret key.
+/// Verify the password with an Argon2id hash using a secret key.
+fn verify_with_key(password: &str, hash: &str, secret_key: &SecretKey) -> Result<bool, String> {
+    let mut verifier = Verifier::default();
+    match verifier
+        .with_hash(hash)
+        .with_password(password)
+        .with_secret_key(secret_key.get_key())
+        .verify()
+    {
+        Ok(result) => Ok(result),
+        Err(_) => Err(String::from(
+            "Cannot verify the password hash using Argon2id algorithm.",
+        )),
+    }
+}
+
+/// Encrypt an user password using Argon2id algorithm and the current app secret key.
+///
+/// The cost parameters are read using `HashParameters::from_env`.
 ///
 /// # Example
 /// ```rust
@@ -36,13 +93,21 @@ fn get_secret_key() -> String {
 /// }
 /// ```
 pub fn encrypt_password(password: &str) -> Result<String, String> {
+    let keyring = Keyring::from_env();
+    let secret_key = keyring.get_current();
+    let parameters = HashParameters::from_env();
+
     let mut hasher = Hasher::default();
     match hasher
+        .configure_memory_size(parameters.memory_size)
+        .configure_iterations(parameters.iterations)
+        .configure_lanes(parameters.lanes)
+        .configure_threads(parameters.lanes)
         .with_password(password)
-        .with_secret_key(get_secret_key())
+        .with_secret_key(secret_key.get_key())
         .hash()
     {
-        Ok(password_hash) => Ok(password_hash),
+        Ok(password_hash) => Ok(format!("{}{}", secret_key.get_id(), password_hash)),
         Err(_) => Err(String::from(
             "Cannot encrypt the password using Argon2id algorithm.",
         )),
@@ -50,7 +115,7 @@ pub fn encrypt_password(password: &str) -> Result<String, String> {
 }
 
 /// Verify the password with the user password hash using Argon2id algorithm and the
-/// app secret key.
+/// secret key used to encrypt it.
 ///
 /// # Example
 /// ```rust
@@ -70,16 +135,83 @@ pub fn encrypt_password(password: &str) -> Result<String, String> {
 /// }
 /// ```
 pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, String> {
-    let mut verifier = Verifier::default();
-    match verifier
-        .with_hash(password_hash)
-        .with_password(password)
-        .with_secret_key(get_secret_key())
-        .verify()
-    {
-        Ok(result) => Ok(result),
-        Err(_) => Err(String::from(
-            "Cannot verify the password hash using Argon2id algorithm.",
-        )),
+    let keyring = Keyring::from_env();
+
+    match split_password_hash(password_hash) {
+        (Some(key_id), hash) => match keyring.get(key_id) {
+            Some(secret_key) => verify_with_key(password, hash, secret_key),
+            None => Err(format!("The secret key `{}` does not exist.", key_id)),
+        },
+        (None, hash) => {
+            // Check the hashes without key id with all secret keys.
+            for secret_key in keyring.get_all() {
+                if verify_with_key(password, hash, secret_key)? {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        }
+    }
+}
+
+/// Check if a password hash was encrypted with a previous secret key or with weaker
+/// parameters than the current parameters, so it must be encrypted again.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_security::password_needs_rehash;
+///
+/// fn main() {
+///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+///     env::set_var("APP_SECRET_KEY_ID", "1");
+///     env::set_var("ARGON2_ITERATIONS", "192");
+///
+///     let hash: &str = "1$argon2id$v=19$m=4096,t=128,p=4$c29tZXNhbHQ$aGFzaA";
+///     assert!(password_needs_rehash(hash));
+/// }
+/// ```
+pub fn password_needs_rehash(password_hash: &str) -> bool {
+    let keyring = Keyring::from_env();
+
+    match split_password_hash(password_hash) {
+        (Some(key_id), _) if key_id == keyring.get_current().get_id() => {}
+        _ => return true,
+    }
+
+    match HashParameters::from_hash(password_hash) {
+        Some(parameters) => parameters.is_weaker_than(&HashParameters::from_env()),
+        None => true,
     }
 }
+
+/// Verify the password with the hash of a random password and ignore the result.
+///
+/// This function takes the same time as `verify_password`, so it helps to avoid
+/// leaking whether a user exists by the response time.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_security::dummy_verify_password;
+///
+/// fn main() {
+///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+///
+///     dummy_verify_password("1234");
+/// }
+/// ```
+pub fn dummy_verify_password(password: &str) {
+    verify_password(password, &DUMMY_PASSWORD_HASH).ok();
+}
+
+/// Hash the random password used by `dummy_verify_password`.
+///
+/// # Panics
+/// If the password cannot be hashed, so the server must call it on startup.
+pub fn init_dummy_password_hash() {
+    Lazy::force(&DUMMY_PASSWORD_HASH);
+}
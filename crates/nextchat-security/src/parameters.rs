@@ -0,0 +1,110 @@
+//! NextChat Security hash parameters module.
+//!
+//! This module contains the Argon2id cost parameters used to encrypt the
+//! user passwords.
+
+use std::env;
+
+/// Default memory size in kibibytes.
+const DEFAULT_MEMORY_SIZE: u32 = 4096;
+
+/// Default number of iterations.
+const DEFAULT_ITERATIONS: u32 = 192;
+
+/// Default number of lanes.
+const DEFAULT_LANES: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HashParameters {
+    pub memory_size: u32,
+    pub iterations: u32,
+    pub lanes: u32,
+}
+
+/// Read a positive number from the environment or use the default value.
+fn get_env_number(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+impl HashParameters {
+    /// Get the parameters from the `ARGON2_MEMORY_SIZE`, `ARGON2_ITERATIONS` and
+    /// `ARGON2_LANES` environment variables.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::env;
+    ///
+    /// use nextchat_security::HashParameters;
+    ///
+    /// fn main() {
+    ///     env::set_var("ARGON2_ITERATIONS", "256");
+    ///
+    ///     let parameters = HashParameters::from_env();
+    ///     assert_eq!(parameters.iterations, 256);
+    /// }
+    /// ```
+    pub fn from_env() -> Self {
+        Self {
+            memory_size: get_env_number("ARGON2_MEMORY_SIZE", DEFAULT_MEMORY_SIZE),
+            iterations: get_env_number("ARGON2_ITERATIONS", DEFAULT_ITERATIONS),
+            lanes: get_env_number("ARGON2_LANES", DEFAULT_LANES),
+        }
+    }
+
+    /// Parse the parameters of an encoded Argon2 hash like
+    /// `$argon2id$v=19$m=4096,t=192,p=4${salt}${hash}`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nextchat_security::HashParameters;
+    ///
+    /// fn main() {
+    ///     let parameters = HashParameters::from_hash("$argon2id$v=19$m=4096,t=192,p=4$c29tZXNhbHQ$aGFzaA");
+    ///     assert_eq!(
+    ///         parameters,
+    ///         Some(HashParameters {
+    ///             memory_size: 4096,
+    ///             iterations: 192,
+    ///             lanes: 4,
+    ///         })
+    ///     );
+    /// }
+    /// ```
+    pub fn from_hash(hash: &str) -> Option<Self> {
+        let segment = hash.split('$').find(|segment| segment.starts_with("m="))?;
+
+        let mut memory_size: Option<u32> = None;
+        let mut iterations: Option<u32> = None;
+        let mut lanes: Option<u32> = None;
+
+        for parameter in segment.split(',') {
+            let mut pair = parameter.splitn(2, '=');
+            let name = pair.next();
+            let value = pair.next().and_then(|value| value.parse::<u32>().ok());
+
+            match name {
+                Some("m") => memory_size = value,
+                Some("t") => iterations = value,
+                Some("p") => lanes = value,
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            memory_size: memory_size?,
+            iterations: iterations?,
+            lanes: lanes?,
+        })
+    }
+
+    /// Check if the memory size or the iterations are lower than the other parameters.
+    ///
+    /// The lanes only change the parallelism of the algorithm, so they are not compared.
+    pub fn is_weaker_than(&self, other: &HashParameters) -> bool {
+        self.memory_size < other.memory_size || self.iterations < other.iterations
+    }
+}
@@ -0,0 +1,322 @@
+//! NextChat Security passkeys module.
+//!
+//! This module contains the WebAuthn registration and authentication ceremonies
+//! of the passkeys. The state of each ceremony is kept in memory until it is
+//! finished or expires.
+//!
+//! # Environment variables
+//! - `WEBAUTHN_RP_ID` _Default_ `localhost` - The domain of the relying party.
+//! - `WEBAUTHN_RP_ORIGIN` _Default_ `http://localhost:5000` - The origin of the clients.
+//! - `WEBAUTHN_RP_NAME` _Default_ `NextChat` - The name shown by the authenticators.
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use url::Url;
+use webauthn_rs::{AuthenticationState, RegistrationState, Webauthn, WebauthnConfig};
+
+pub use webauthn_rs::proto::{
+    CreationChallengeResponse, Credential, PublicKeyCredential, RegisterPublicKeyCredential,
+    RequestChallengeResponse,
+};
+
+use crate::generate_random_token;
+
+/// Time to finish a ceremony (5 minutes).
+const CEREMONY_LIFETIME: Duration = Duration::from_secs(60 * 5);
+
+/// Max ceremonies in progress, the signin ceremonies are started without authentication.
+const MAX_CEREMONIES: usize = 10_000;
+
+/// Max ceremonies in progress started from the same IP address.
+const MAX_CEREMONIES_PER_IP: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct PasskeysConfig {
+    rp_name: String,
+    rp_id: String,
+    rp_origin: Url,
+}
+
+impl PasskeysConfig {
+    /// Create a new relying party configuration.
+    pub fn new(rp_name: &str, rp_id: &str, rp_origin: &str) -> Self {
+        let rp_origin: Url = match Url::parse(rp_origin) {
+            Ok(rp_origin) => rp_origin,
+            Err(_) => {
+                panic!(
+                    "Security Error -> `{}` is not a valid WebAuthn origin.",
+                    rp_origin
+                );
+            }
+        };
+
+        Self {
+            rp_name: String::from(rp_name),
+            rp_id: String::from(rp_id),
+            rp_origin,
+        }
+    }
+
+    /// Get the relying party configuration from the environment variables.
+    pub fn from_env() -> Self {
+        Self::new(
+            &env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| String::from("NextChat")),
+            &env::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| String::from("localhost")),
+            &env::var("WEBAUTHN_RP_ORIGIN")
+                .unwrap_or_else(|_| String::from("http://localhost:5000")),
+        )
+    }
+}
+
+impl WebauthnConfig for PasskeysConfig {
+    fn get_relying_party_name(&self) -> &str {
+        &self.rp_name
+    }
+
+    fn get_origin(&self) -> &Url {
+        &self.rp_origin
+    }
+
+    fn get_relying_party_id(&self) -> &str {
+        &self.rp_id
+    }
+}
+
+enum CeremonyState {
+    Registration(RegistrationState),
+    Authentication(AuthenticationState),
+}
+
+struct Ceremony {
+    owner: String,
+    ip: String,
+    state: CeremonyState,
+    created_at: Instant,
+}
+
+#[derive(Default)]
+struct Ceremonies {
+    by_id: HashMap<String, Ceremony>,
+    by_ip: HashMap<String, usize>,
+}
+
+impl Ceremonies {
+    /// Remove a ceremony and discount it from its IP address.
+    fn remove(&mut self, ceremony_id: &str) -> Option<Ceremony> {
+        let ceremony = self.by_id.remove(ceremony_id)?;
+
+        if let Some(count) = self.by_ip.get_mut(&ceremony.ip) {
+            *count -= 1;
+
+            if *count == 0 {
+                self.by_ip.remove(&ceremony.ip);
+            }
+        }
+
+        Some(ceremony)
+    }
+
+    /// Remove the expired ceremonies.
+    fn purge(&mut self) {
+        let expired: Vec<String> = self
+            .by_id
+            .iter()
+            .filter(|(_, ceremony)| ceremony.created_at.elapsed() >= CEREMONY_LIFETIME)
+            .map(|(ceremony_id, _)| ceremony_id.clone())
+            .collect();
+
+        for ceremony_id in expired.iter() {
+            self.remove(ceremony_id);
+        }
+    }
+
+    /// Get the ceremonies in progress started from an IP address.
+    fn count_of(&self, ip: &str) -> usize {
+        self.by_ip.get(ip).copied().unwrap_or(0)
+    }
+
+    /// Check if the limit of ceremonies in progress, in total or from an IP
+    /// address, is reached.
+    fn is_full(&self, ip: &str) -> bool {
+        self.by_id.len() >= MAX_CEREMONIES || self.count_of(ip) >= MAX_CEREMONIES_PER_IP
+    }
+}
+
+pub struct Passkeys {
+    webauthn: Webauthn<PasskeysConfig>,
+    ceremonies: Mutex<Ceremonies>,
+}
+
+impl Passkeys {
+    /// Create a new passkeys manager.
+    pub fn new(config: PasskeysConfig) -> Self {
+        Self {
+            webauthn: Webauthn::new(config),
+            ceremonies: Mutex::new(Ceremonies::default()),
+        }
+    }
+
+    /// Get the passkeys manager from the environment variables.
+    pub fn from_env() -> Self {
+        Self::new(PasskeysConfig::from_env())
+    }
+
+    /// Store the state of a new ceremony started from an IP address and get its id.
+    fn add_ceremony(&self, owner: &str, ip: &str, state: CeremonyState) -> Result<String, String> {
+        let ceremony_id: String = generate_random_token();
+        let mut ceremonies = self.ceremonies.lock().expect("Cannot lock the ceremonies.");
+
+        // Forget the expired ceremonies only when a limit is reached.
+        if ceremonies.is_full(ip) {
+            ceremonies.purge();
+        }
+
+        if ceremonies.is_full(ip) {
+            return Err(String::from(
+                "Too many ceremonies in progress, try again later.",
+            ));
+        }
+
+        let count: usize = ceremonies.count_of(ip);
+        ceremonies.by_ip.insert(String::from(ip), count + 1);
+        ceremonies.by_id.insert(
+            ceremony_id.clone(),
+            Ceremony {
+                owner: String::from(owner),
+                ip: String::from(ip),
+                state,
+                created_at: Instant::now(),
+            },
+        );
+
+        Ok(ceremony_id)
+    }
+
+    /// Remove a ceremony, so it can only be finished once.
+    fn take_ceremony(&self, ceremony_id: &str) -> Result<Ceremony, String> {
+        match self
+            .ceremonies
+            .lock()
+            .expect("Cannot lock the ceremonies.")
+            .remove(ceremony_id)
+        {
+            Some(ceremony) if ceremony.created_at.elapsed() < CEREMONY_LIFETIME => Ok(ceremony),
+            _ => Err(String::from("The ceremony is invalid or has expired.")),
+        }
+    }
+
+    /// Start the registration of a new passkey and get the ceremony id and the
+    /// options for `navigator.credentials.create()`.
+    ///
+    /// The `owner` is an unique id of the user that must finish the ceremony, and
+    /// the `ip` is the IP address that started it.
+    pub fn start_registration(
+        &self,
+        owner: &str,
+        ip: &str,
+        user_name: &str,
+    ) -> Result<(String, CreationChallengeResponse), String> {
+        match self.webauthn.generate_challenge_register(user_name, false) {
+            Ok((options, state)) => Ok((
+                self.add_ceremony(owner, ip, CeremonyState::Registration(state))?,
+                options,
+            )),
+            Err(_) => Err(String::from("Cannot start the passkey registration.")),
+        }
+    }
+
+    /// Verify the attestation of the new passkey and get its credential.
+    pub fn finish_registration(
+        &self,
+        ceremony_id: &str,
+        owner: &str,
+        credential: &RegisterPublicKeyCredential,
+    ) -> Result<Credential, String> {
+        let state: RegistrationState = match self.take_ceremony(ceremony_id)? {
+            Ceremony {
+                owner: ceremony_owner,
+                state: CeremonyState::Registration(state),
+                ..
+            } if ceremony_owner == owner => state,
+            _ => {
+                return Err(String::from("The ceremony is invalid or has expired."));
+            }
+        };
+
+        // The unique credential ids are checked by the database.
+        match self
+            .webauthn
+            .register_credential(credential, &state, |_| Ok(false))
+        {
+            Ok((credential, _)) => Ok(credential),
+            Err(_) => Err(String::from("Cannot verify the passkey.")),
+        }
+    }
+
+    /// Start the authentication with the passkeys of the owner and get the
+    /// ceremony id and the options for `navigator.credentials.get()`.
+    pub fn start_authentication(
+        &self,
+        owner: &str,
+        ip: &str,
+        credentials: Vec<Credential>,
+    ) -> Result<(String, RequestChallengeResponse), String> {
+        if credentials.is_empty() {
+            return Err(String::from("Cannot sign in with a passkey."));
+        }
+
+        match self.webauthn.generate_challenge_authenticate(credentials) {
+            Ok((options, state)) => Ok((
+                self.add_ceremony(owner, ip, CeremonyState::Authentication(state))?,
+                options,
+            )),
+            Err(_) => Err(String::from("Cannot sign in with a passkey.")),
+        }
+    }
+
+    /// Verify the assertion of a passkey and get the owner, the credential id and
+    /// the new signature counter of the passkey.
+    pub fn finish_authentication(
+        &self,
+        ceremony_id: &str,
+        credential: &PublicKeyCredential,
+    ) -> Result<(String, String, u32), String> {
+        let (owner, state) = match self.take_ceremony(ceremony_id)? {
+            Ceremony {
+                owner,
+                state: CeremonyState::Authentication(state),
+                ..
+            } => (owner, state),
+            _ => {
+                return Err(String::from("The ceremony is invalid or has expired."));
+            }
+        };
+
+        match self.webauthn.authenticate_credential(credential, &state) {
+            Ok((credential_id, data)) => Ok((
+                owner,
+                base64::encode_config(&credential_id, base64::URL_SAFE_NO_PAD),
+                data.counter,
+            )),
+            Err(_) => Err(String::from("Cannot verify the passkey.")),
+        }
+    }
+
+    /// Remove the expired ceremonies.
+    pub fn purge_ceremonies(&self) {
+        self.ceremonies
+            .lock()
+            .expect("Cannot lock the ceremonies.")
+            .purge();
+    }
+}
+
+/// Get the credential id of a passkey encoded in url safe base64.
+pub fn get_credential_id(credential: &Credential) -> String {
+    base64::encode_config(&credential.cred_id, base64::URL_SAFE_NO_PAD)
+}
@@ -0,0 +1,256 @@
+//! NextChat Security password policy module.
+//!
+//! This module contains the strength requirements of the user passwords. The
+//! strength is estimated in bits of entropy, ignoring the repeated and sequential
+//! characters, and the passwords are checked against a list of common passwords
+//! and, optionally, a local list of breached passwords.
+//!
+//! The breached passwords list is a directory with a file for each prefix of 5
+//! hexadecimal characters of the SHA-1 hashes, `{prefix}.txt`, with a line for
+//! each hash suffix: `{suffix}:{count}`. It is the same format of the Have I Been
+//! Pwned range API, so only the file of the prefix is read to check a password.
+//!
+//! # Environment variables
+//! - `PASSWORD_MIN_LENGTH` _Default_ `8` - Min length of the passwords in characters.
+//! - `PASSWORD_MAX_LENGTH` _Default_ `128` - Max length of the passwords in characters.
+//! - `PASSWORD_BREACHED_DIR` _Optional_ - Directory of the breached passwords list.
+
+use std::{
+    env,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use sha1::{Digest, Sha1};
+
+/// Default min length of the passwords in characters.
+const DEFAULT_MIN_LENGTH: usize = 8;
+
+/// Default max length of the passwords in characters.
+const DEFAULT_MAX_LENGTH: usize = 128;
+
+/// Min estimated entropy of the passwords in bits.
+const MIN_ENTROPY_BITS: f64 = 50.0;
+
+/// Min characters that are not repeated or sequential.
+const MIN_EFFECTIVE_LENGTH: usize = 4;
+
+/// Most common passwords, rejected without the breached passwords list.
+const COMMON_PASSWORDS: [&str; 24] = [
+    "123456789",
+    "1234567890",
+    "12345678",
+    "abc123456",
+    "baseball",
+    "dragon123",
+    "football",
+    "iloveyou",
+    "letmein123",
+    "master123",
+    "monkey123",
+    "nextchat",
+    "nextchat123",
+    "passw0rd",
+    "password",
+    "password1",
+    "password123",
+    "princess",
+    "qwerty123",
+    "qwertyuiop",
+    "starwars",
+    "sunshine",
+    "trustno1",
+    "welcome123",
+];
+
+/// Read a positive number from the environment or use the default value.
+fn get_env_number(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+/// Get the number of characters that are not repeated or sequential, like the
+/// `b` and `c` of `abc` or the second `a` of `aa`.
+fn get_effective_length(password: &str) -> usize {
+    let mut length: usize = 0;
+    let mut previous: Option<i64> = None;
+
+    for character in password.chars() {
+        let code: i64 = character as i64;
+
+        match previous {
+            Some(previous) if (code - previous).abs() <= 1 => {}
+            _ => length += 1,
+        }
+
+        previous = Some(code);
+    }
+
+    length
+}
+
+/// Estimate the entropy of a password in bits, using the size of the character
+/// groups of the password and its effective length.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::estimate_password_entropy;
+///
+/// fn main() {
+///     assert!(estimate_password_entropy("aaaaaaaaaaaa") < estimate_password_entropy("ab7#kP"));
+///     assert!(estimate_password_entropy("correct-horse-battery") > 50.0);
+/// }
+/// ```
+pub fn estimate_password_entropy(password: &str) -> f64 {
+    let mut pool_size: u32 = 0;
+
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool_size += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii_punctuation() || c == ' ')
+    {
+        pool_size += 33;
+    }
+    if password.chars().any(|c| !c.is_ascii()) {
+        pool_size += 100;
+    }
+
+    if pool_size == 0 {
+        return 0.0;
+    }
+
+    get_effective_length(password) as f64 * f64::from(pool_size).log2()
+}
+
+/// Check if a password is in a breached passwords list directory, see the
+/// format at the module documentation.
+///
+/// The passwords are not breached when the prefix file does not exist.
+pub fn is_breached_password(password: &str, directory: &Path) -> bool {
+    let hash: String = format!("{:X}", Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+
+    let file = match File::open(directory.join(format!("{}.txt", prefix))) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+
+    BufReader::new(file).lines().any(|line| match line {
+        Ok(line) => line
+            .split(':')
+            .next()
+            .map(|line_suffix| line_suffix.trim().eq_ignore_ascii_case(suffix))
+            .unwrap_or(false),
+        Err(_) => false,
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub breached_passwords_dir: Option<PathBuf>,
+}
+
+impl PasswordPolicy {
+    /// Get the policy from the `PASSWORD_MIN_LENGTH`, `PASSWORD_MAX_LENGTH` and
+    /// `PASSWORD_BREACHED_DIR` environment variables.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::env;
+    ///
+    /// use nextchat_security::PasswordPolicy;
+    ///
+    /// fn main() {
+    ///     env::set_var("PASSWORD_MAX_LENGTH", "256");
+    ///
+    ///     let policy = PasswordPolicy::from_env();
+    ///     assert_eq!(policy.max_length, 256);
+    /// }
+    /// ```
+    pub fn from_env() -> Self {
+        let min_length: usize = get_env_number("PASSWORD_MIN_LENGTH", DEFAULT_MIN_LENGTH);
+
+        Self {
+            min_length,
+            max_length: get_env_number("PASSWORD_MAX_LENGTH", DEFAULT_MAX_LENGTH).max(min_length),
+            breached_passwords_dir: env::var("PASSWORD_BREACHED_DIR")
+                .ok()
+                .filter(|directory| !directory.is_empty())
+                .map(PathBuf::from),
+        }
+    }
+
+    /// Check the password requirements, the `user_inputs` are values that cannot
+    /// be part of the password, like the username.
+    ///
+    /// Get the reason why the password is not allowed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nextchat_security::PasswordPolicy;
+    ///
+    /// fn main() {
+    ///     let policy = PasswordPolicy {
+    ///         min_length: 8,
+    ///         max_length: 128,
+    ///         breached_passwords_dir: None,
+    ///     };
+    ///
+    ///     assert!(policy.check("correct-horse-battery", &["NextChat"]).is_ok());
+    ///     assert!(policy.check("password", &["NextChat"]).is_err());
+    ///     assert!(policy.check("NextChat2021!", &["NextChat"]).is_err());
+    /// }
+    /// ```
+    pub fn check(&self, password: &str, user_inputs: &[&str]) -> Result<(), &'static str> {
+        let length: usize = password.chars().count();
+
+        if password.is_empty() {
+            return Err("You must enter the password.");
+        } else if length < self.min_length {
+            return Err("The password is too short, use a longer password.");
+        } else if length > self.max_length {
+            return Err("The password is too long.");
+        }
+
+        let lowercase: String = password.to_lowercase();
+
+        if user_inputs
+            .iter()
+            .any(|input| input.chars().count() >= 3 && lowercase.contains(&input.to_lowercase()))
+        {
+            return Err("The password cannot contain your username.");
+        } else if COMMON_PASSWORDS.contains(&lowercase.as_str()) {
+            return Err("The password is too common, choose another password.");
+        } else if get_effective_length(password) < MIN_EFFECTIVE_LENGTH {
+            return Err(
+                "The password cannot be a repeated character or a sequence like `abcd` or `1234`.",
+            );
+        } else if estimate_password_entropy(password) < MIN_ENTROPY_BITS {
+            return Err(
+                "The password is too weak, use a longer password or mix letters, numbers and symbols.",
+            );
+        }
+
+        if let Some(directory) = &self.breached_passwords_dir {
+            if is_breached_password(password, directory) {
+                return Err("The password appeared in a data breach, choose another password.");
+            }
+        }
+
+        Ok(())
+    }
+}
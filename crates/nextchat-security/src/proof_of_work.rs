@@ -0,0 +1,173 @@
+//! NextChat Security proof of work module.
+//!
+//! This module contains the hashcash-style proof of work used to slow down the
+//! automated signups without a third-party captcha. The client must find a
+//! solution whose SHA-256 hash of `{challenge}:{solution}` starts with the
+//! number of zero bits of the difficulty, so each extra bit doubles the work.
+//!
+//! The difficulty is raised automatically when the number of recent signups is
+//! greater than the threshold: `DIFFICULTY_STEP` bits for each time the
+//! threshold is doubled, up to the max difficulty.
+//!
+//! # Environment variables
+//! - `POW_DIFFICULTY` _Default_ `20` - Base difficulty in bits, `0` disables the proof of work.
+//! - `POW_MAX_DIFFICULTY` _Default_ `26` - Max difficulty in bits.
+//! - `POW_SIGNUP_THRESHOLD` _Default_ `30` - Signups in the rate window before the difficulty
+//!   is raised, `0` disables the adaptive difficulty.
+
+use std::env;
+
+use sha2::{Digest, Sha256};
+
+/// Default base difficulty in bits.
+const DEFAULT_DIFFICULTY: u8 = 20;
+
+/// Default max difficulty in bits.
+const DEFAULT_MAX_DIFFICULTY: u8 = 26;
+
+/// Default signups in the rate window before the difficulty is raised.
+const DEFAULT_SIGNUP_THRESHOLD: u64 = 30;
+
+/// Bits added to the difficulty each time the signups double the threshold.
+const DIFFICULTY_STEP: u8 = 2;
+
+/// Max difficulty allowed by the configuration, a SHA-256 hash has 256 bits but
+/// the solutions must be found in a reasonable time.
+const MAX_ALLOWED_DIFFICULTY: u8 = 32;
+
+/// Read a number from the environment or use the default value.
+fn get_env_number<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse::<T>().ok())
+        .unwrap_or(default)
+}
+
+/// Count the zero bits at the start of a hash.
+fn count_leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits: u32 = 0;
+
+    for byte in hash.iter() {
+        bits += byte.leading_zeros();
+
+        if *byte != 0 {
+            break;
+        }
+    }
+
+    bits
+}
+
+/// Verify that the solution of a challenge has the difficulty bits.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::{solve_proof_of_work, verify_proof_of_work};
+///
+/// fn main() {
+///     let solution: String = solve_proof_of_work("NextChat", 8);
+///
+///     assert!(verify_proof_of_work("NextChat", &solution, 8));
+///     assert!(!verify_proof_of_work("NextChatORG", &solution, 16));
+/// }
+/// ```
+pub fn verify_proof_of_work(challenge: &str, solution: &str, difficulty: u8) -> bool {
+    let hash = Sha256::digest(format!("{}:{}", challenge, solution).as_bytes());
+
+    count_leading_zero_bits(&hash) >= u32::from(difficulty)
+}
+
+/// Find a solution of a challenge with the difficulty bits.
+///
+/// The solution is the decimal number of attempts, it is used by the tests and
+/// as reference of the client implementations.
+pub fn solve_proof_of_work(challenge: &str, difficulty: u8) -> String {
+    let mut attempt: u64 = 0;
+
+    loop {
+        let solution: String = attempt.to_string();
+        if verify_proof_of_work(challenge, &solution, difficulty) {
+            return solution;
+        }
+
+        attempt += 1;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofOfWork {
+    pub difficulty: u8,
+    pub max_difficulty: u8,
+    pub signup_threshold: u64,
+}
+
+impl ProofOfWork {
+    /// Get the configuration from the `POW_DIFFICULTY`, `POW_MAX_DIFFICULTY` and
+    /// `POW_SIGNUP_THRESHOLD` environment variables.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::env;
+    ///
+    /// use nextchat_security::ProofOfWork;
+    ///
+    /// fn main() {
+    ///     env::set_var("POW_DIFFICULTY", "0");
+    ///
+    ///     assert!(!ProofOfWork::from_env().is_enabled());
+    /// }
+    /// ```
+    pub fn from_env() -> Self {
+        let difficulty: u8 =
+            get_env_number("POW_DIFFICULTY", DEFAULT_DIFFICULTY).min(MAX_ALLOWED_DIFFICULTY);
+
+        Self {
+            difficulty,
+            max_difficulty: get_env_number("POW_MAX_DIFFICULTY", DEFAULT_MAX_DIFFICULTY)
+                .min(MAX_ALLOWED_DIFFICULTY)
+                .max(difficulty),
+            signup_threshold: get_env_number("POW_SIGNUP_THRESHOLD", DEFAULT_SIGNUP_THRESHOLD),
+        }
+    }
+
+    /// Check if the signups require the proof of work.
+    pub fn is_enabled(&self) -> bool {
+        self.difficulty > 0
+    }
+
+    /// Get the difficulty of the new challenges using the number of signups in
+    /// the rate window.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nextchat_security::ProofOfWork;
+    ///
+    /// fn main() {
+    ///     let proof_of_work = ProofOfWork {
+    ///         difficulty: 20,
+    ///         max_difficulty: 26,
+    ///         signup_threshold: 30,
+    ///     };
+    ///
+    ///     assert_eq!(proof_of_work.get_difficulty(10), 20);
+    ///     assert_eq!(proof_of_work.get_difficulty(31), 22);
+    ///     assert_eq!(proof_of_work.get_difficulty(61), 24);
+    ///     assert_eq!(proof_of_work.get_difficulty(10_000), 26);
+    /// }
+    /// ```
+    pub fn get_difficulty(&self, recent_signups: u64) -> u8 {
+        if !self.is_enabled() || self.signup_threshold == 0 {
+            return self.difficulty;
+        }
+
+        let mut difficulty: u8 = self.difficulty;
+        let mut threshold: u64 = self.signup_threshold;
+
+        while recent_signups > threshold && difficulty < self.max_difficulty {
+            difficulty = difficulty.saturating_add(DIFFICULTY_STEP);
+            threshold = threshold.saturating_mul(2);
+        }
+
+        difficulty.min(self.max_difficulty)
+    }
+}
@@ -0,0 +1,209 @@
+//! NextChat Security tokens module.
+//!
+//! This module contains the functions to sign and verify the session tokens
+//! using the HS256 algorithm and the app secret keys. Also, contains the functions
+//! to generate the opaque refresh tokens and the hash that is stored in the database.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Keyring;
+
+/// Get the current unix timestamp in seconds.
+pub fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Cannot get the current timestamp.")
+        .as_secs()
+}
+
+/// Sign the token claims using the current app secret key.
+///
+/// The claims must contain an `exp` field with the expiration timestamp. The id of
+/// the secret key is added to the `kid` field of the token header.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_security::{encode_token, get_timestamp};
+/// use serde::Serialize;
+///
+/// fn main() {
+///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+///
+///     #[derive(Serialize)]
+///     struct Claims {
+///         sub: String,
+///         exp: u64,
+///     }
+///
+///     let token = encode_token(&Claims {
+///         sub: String::from("NextChat"),
+///         exp: get_timestamp() + 60,
+///     });
+///     assert!(token.is_ok());
+/// }
+/// ```
+pub fn encode_token<T: Serialize>(claims: &T) -> Result<String, String> {
+    let keyring = Keyring::from_env();
+    let secret_key = keyring.get_current();
+
+    let header = Header {
+        kid: Some(String::from(secret_key.get_id())),
+        ..Header::default()
+    };
+
+    match encode(
+        &header,
+        claims,
+        &EncodingKey::from_secret(secret_key.get_key().as_bytes()),
+    ) {
+        Ok(token) => Ok(token),
+        Err(_) => Err(String::from("Cannot sign the token.")),
+    }
+}
+
+/// Verify the token signature and expiration using the app secret key of the
+/// `kid` header field and get its claims.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_security::{decode_token, encode_token, get_timestamp};
+/// use serde::{Deserialize, Serialize};
+///
+/// fn main() {
+///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+///
+///     #[derive(Serialize, Deserialize)]
+///     struct Claims {
+///         sub: String,
+///         exp: u64,
+///     }
+///
+///     let token: String = encode_token(&Claims {
+///         sub: String::from("NextChat"),
+///         exp: get_timestamp() + 60,
+///     })
+///     .unwrap();
+///
+///     let claims = decode_token::<Claims>(&token);
+///     assert!(claims.is_ok());
+///     assert_eq!(claims.unwrap().sub, String::from("NextChat"));
+/// }
+/// ```
+pub fn decode_token<T: DeserializeOwned>(token: &str) -> Result<T, String> {
+    let keyring = Keyring::from_env();
+
+    let secret_key = match decode_header(token).ok().and_then(|header| header.kid) {
+        Some(key_id) => keyring.get(&key_id),
+        None => Some(keyring.get_current()),
+    };
+
+    let secret_key = match secret_key {
+        Some(secret_key) => secret_key,
+        None => {
+            return Err(String::from("The token is invalid or has expired."));
+        }
+    };
+
+    match decode::<T>(
+        token,
+        &DecodingKey::from_secret(secret_key.get_key().as_bytes()),
+        &Validation::default(),
+    ) {
+        Ok(data) => Ok(data.claims),
+        Err(_) => Err(String::from("The token is invalid or has expired.")),
+    }
+}
+
+/// Generate a random url safe token with 256 bits of entropy.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::generate_random_token;
+///
+/// fn main() {
+///     let token: String = generate_random_token();
+///     assert_eq!(token.len(), 43);
+///     assert_ne!(token, generate_random_token());
+/// }
+/// ```
+pub fn generate_random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Alphabet of the recovery codes (RFC 4648 base32 in lowercase).
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Generate a random recovery code like `xxxx-xxxx-xxxx-xxxx` with 80 bits of entropy.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::{generate_recovery_code, normalize_recovery_code};
+///
+/// fn main() {
+///     let code: String = generate_recovery_code();
+///     assert_eq!(code.len(), 19);
+///     assert_eq!(normalize_recovery_code(&code).len(), 16);
+/// }
+/// ```
+pub fn generate_recovery_code() -> String {
+    let characters: Vec<char> = (0..16)
+        .map(|_| RECOVERY_CODE_ALPHABET[OsRng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    characters
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// Remove the separators and whitespaces of a recovery code entered by a user and
+/// convert it to lowercase.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Format a code entered by a user like the generated recovery codes,
+/// `xxxx-xxxx-xxxx-xxxx`.
+pub fn format_recovery_code(code: &str) -> String {
+    normalize_recovery_code(code)
+        .chars()
+        .collect::<Vec<char>>()
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// Get the SHA-256 hash of a token as a hexadecimal string.
+///
+/// The random tokens have enough entropy to be stored using a fast hash
+/// instead of the Argon2id algorithm.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::hash_token;
+///
+/// fn main() {
+///     assert_eq!(hash_token("NextChat"), hash_token("NextChat"));
+///     assert_eq!(hash_token("NextChat").len(), 64);
+/// }
+/// ```
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,227 @@
+//! NextChat Security TOTP module.
+//!
+//! This module contains the functions to generate and verify the RFC 6238
+//! time-based one-time passwords (HMAC-SHA1, 6 digits and 30 seconds steps)
+//! and to encrypt the TOTP secrets at rest using AES-256-GCM and the app secret key.
+
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    Aes256Gcm,
+};
+use base32::Alphabet;
+use hmac::{Hmac, Mac, NewMac};
+use rand::{rngs::OsRng, RngCore};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::{Keyring, SecretKey};
+
+/// Seconds of each TOTP step.
+const STEP: u64 = 30;
+
+/// Number of digits of the codes.
+const DIGITS: u32 = 6;
+
+/// Steps accepted before and after the current step to allow clock drift.
+const WINDOW: u64 = 1;
+
+const ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// Generate a random TOTP secret of 160 bits encoded in base32.
+pub fn generate_totp_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+
+    base32::encode(ALPHABET, &bytes)
+}
+
+/// Get the TOTP code of a base32 secret for an unix timestamp.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::get_totp_code;
+///
+/// fn main() {
+///     // RFC 6238 test vector.
+///     let code = get_totp_code("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", 59);
+///     assert_eq!(code, Ok(String::from("287082")));
+/// }
+/// ```
+pub fn get_totp_code(secret: &str, timestamp: u64) -> Result<String, String> {
+    get_step_code(secret, timestamp / STEP)
+}
+
+/// Get the TOTP code of a step.
+fn get_step_code(secret: &str, step: u64) -> Result<String, String> {
+    let key = match base32::decode(ALPHABET, secret) {
+        Some(key) => key,
+        None => {
+            return Err(String::from(
+                "The TOTP secret is not a valid base32 string.",
+            ));
+        }
+    };
+
+    let mut mac = match Hmac::<Sha1>::new_varkey(&key) {
+        Ok(mac) => mac,
+        Err(_) => {
+            return Err(String::from("Cannot create the TOTP code."));
+        }
+    };
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226).
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = (u32::from(hash[offset]) & 0x7f) << 24
+        | u32::from(hash[offset + 1]) << 16
+        | u32::from(hash[offset + 2]) << 8
+        | u32::from(hash[offset + 3]);
+
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// Verify a TOTP code for an unix timestamp and get the matched step.
+///
+/// The codes of the previous and next steps are accepted too. Store the matched
+/// step and reject the codes of the same or previous steps to avoid replays.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::verify_totp_code;
+///
+/// fn main() {
+///     let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+///
+///     assert_eq!(verify_totp_code(secret, "287082", 59), Some(1));
+///     assert_eq!(verify_totp_code(secret, "287082", 89), Some(1));
+///     assert_eq!(verify_totp_code(secret, "287082", 150), None);
+/// }
+/// ```
+pub fn verify_totp_code(secret: &str, code: &str, timestamp: u64) -> Option<u64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+
+    let current_step = timestamp / STEP;
+
+    (current_step.saturating_sub(WINDOW)..=current_step + WINDOW).find(|step| {
+        get_step_code(secret, *step)
+            .map(|expected| expected == code)
+            .unwrap_or(false)
+    })
+}
+
+/// Percent encode a value of the otpauth URI.
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Get the `otpauth://` URI of a TOTP secret for the authenticator apps.
+///
+/// # Example
+/// ```rust
+/// use nextchat_security::get_totp_uri;
+///
+/// fn main() {
+///     assert_eq!(
+///         get_totp_uri("GEZDGNBVGY3TQOJQ", "NextChat", "danielsolartech"),
+///         String::from("otpauth://totp/NextChat:danielsolartech?secret=GEZDGNBVGY3TQOJQ&issuer=NextChat&algorithm=SHA1&digits=6&period=30")
+///     );
+/// }
+/// ```
+pub fn get_totp_uri(secret: &str, issuer: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = encode_uri_component(issuer),
+        account = encode_uri_component(account),
+        secret = secret,
+        digits = DIGITS,
+        period = STEP,
+    )
+}
+
+/// Get the AES-256-GCM cipher of a secret key.
+fn get_cipher(secret_key: &SecretKey) -> Aes256Gcm {
+    let key = Sha256::digest(secret_key.get_key().as_bytes());
+    Aes256Gcm::new(GenericArray::from_slice(&key))
+}
+
+/// Encrypt a secret using AES-256-GCM and the current app secret key.
+///
+/// The result is `{key_id}${base64 nonce and ciphertext}`.
+///
+/// # Example
+/// ```rust
+/// use std::env;
+///
+/// use nextchat_security::{decrypt_secret, encrypt_secret};
+///
+/// fn main() {
+///     env::set_var("APP_SECRET_KEY", "NextChatORG");
+///
+///     let encrypted: String = encrypt_secret("GEZDGNBVGY3TQOJQ").unwrap();
+///     assert_eq!(decrypt_secret(&encrypted), Ok(String::from("GEZDGNBVGY3TQOJQ")));
+/// }
+/// ```
+pub fn encrypt_secret(secret: &str) -> Result<String, String> {
+    let keyring = Keyring::from_env();
+    let secret_key = keyring.get_current();
+
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+
+    match get_cipher(secret_key).encrypt(GenericArray::from_slice(&nonce), secret.as_bytes()) {
+        Ok(ciphertext) => {
+            let mut data: Vec<u8> = nonce.to_vec();
+            data.extend(ciphertext);
+
+            Ok(format!(
+                "{}${}",
+                secret_key.get_id(),
+                base64::encode_config(&data, base64::URL_SAFE_NO_PAD)
+            ))
+        }
+        Err(_) => Err(String::from("Cannot encrypt the secret.")),
+    }
+}
+
+/// Decrypt a secret encrypted with `encrypt_secret` using the secret key of its key id.
+pub fn decrypt_secret(encrypted: &str) -> Result<String, String> {
+    let keyring = Keyring::from_env();
+
+    let mut parts = encrypted.splitn(2, '$');
+    let (secret_key, data) = match (parts.next().and_then(|id| keyring.get(id)), parts.next()) {
+        (Some(secret_key), Some(data)) => (secret_key, data),
+        _ => {
+            return Err(String::from("Cannot decrypt the secret."));
+        }
+    };
+
+    let data = match base64::decode_config(data, base64::URL_SAFE_NO_PAD) {
+        Ok(data) if data.len() > 12 => data,
+        _ => {
+            return Err(String::from("Cannot decrypt the secret."));
+        }
+    };
+
+    match get_cipher(secret_key).decrypt(GenericArray::from_slice(&data[..12]), &data[12..]) {
+        Ok(secret) => {
+            String::from_utf8(secret).map_err(|_| String::from("Cannot decrypt the secret."))
+        }
+        Err(_) => Err(String::from("Cannot decrypt the secret.")),
+    }
+}
@@ -0,0 +1,44 @@
+use nextchat_security::{Keyring, SecretKey};
+
+#[test]
+fn test_keyring_rotation() {
+    std::env::set_var("APP_SECRET_KEY", "OldNextChatORG");
+    std::env::set_var("APP_SECRET_KEY_ID", "1");
+    std::env::remove_var("APP_PREVIOUS_SECRET_KEYS");
+
+    let password = "1234";
+    let old_hash = nextchat_security::encrypt_password(password).unwrap();
+    assert!(old_hash.starts_with("1$argon2id$"));
+    assert!(!nextchat_security::password_needs_rehash(&old_hash));
+
+    // Rotate the secret key.
+    std::env::set_var("APP_SECRET_KEY", "NextChatORG");
+    std::env::set_var("APP_SECRET_KEY_ID", "2");
+    std::env::set_var("APP_PREVIOUS_SECRET_KEYS", "1:OldNextChatORG");
+
+    let keyring = Keyring::from_env();
+    assert_eq!(keyring.get_current(), &SecretKey::new("2", "NextChatORG"));
+    assert_eq!(
+        keyring.get("1"),
+        Some(&SecretKey::new("1", "OldNextChatORG"))
+    );
+    assert_eq!(keyring.get("3"), None);
+
+    assert!(nextchat_security::verify_password(password, &old_hash).unwrap());
+    assert!(!nextchat_security::verify_password("4321", &old_hash).unwrap());
+    assert!(nextchat_security::password_needs_rehash(&old_hash));
+
+    let new_hash = nextchat_security::encrypt_password(password).unwrap();
+    assert!(new_hash.starts_with("2$argon2id$"));
+    assert!(nextchat_security::verify_password(password, &new_hash).unwrap());
+    assert!(!nextchat_security::password_needs_rehash(&new_hash));
+
+    // Legacy hashes without key id are checked with all secret keys.
+    let legacy_hash = &old_hash[1..];
+    assert!(nextchat_security::verify_password(password, legacy_hash).unwrap());
+    assert!(nextchat_security::password_needs_rehash(legacy_hash));
+
+    // Remove the previous secret key.
+    std::env::remove_var("APP_PREVIOUS_SECRET_KEYS");
+    assert!(nextchat_security::verify_password(password, &old_hash).is_err());
+}
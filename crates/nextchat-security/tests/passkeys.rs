@@ -0,0 +1,94 @@
+use nextchat_security::{get_credential_id, Passkeys, PasskeysConfig};
+use webauthn_authenticator_rs::{softtok::U2FSoft, WebauthnAuthenticator};
+
+const ORIGIN: &str = "https://localhost:8080";
+
+const IP: &str = "127.0.0.1";
+
+fn get_passkeys() -> Passkeys {
+    Passkeys::new(PasskeysConfig::new("NextChat", "localhost", ORIGIN))
+}
+
+#[test]
+fn test_passkey_ceremonies() {
+    let passkeys = get_passkeys();
+    let mut authenticator = WebauthnAuthenticator::new(U2FSoft::new());
+
+    // Registration.
+    let (ceremony_id, options) = passkeys
+        .start_registration("user-1", IP, "NextChat")
+        .unwrap();
+    let registration = authenticator.do_registration(ORIGIN, options).unwrap();
+
+    // The ceremony belongs to other user.
+    let credential = passkeys.finish_registration(&ceremony_id, "user-2", &registration);
+    assert!(credential.is_err());
+
+    let (ceremony_id, options) = passkeys
+        .start_registration("user-1", IP, "NextChat")
+        .unwrap();
+    let registration = authenticator.do_registration(ORIGIN, options).unwrap();
+    let credential = passkeys
+        .finish_registration(&ceremony_id, "user-1", &registration)
+        .unwrap();
+
+    // The ceremony can only be finished once.
+    assert!(passkeys
+        .finish_registration(&ceremony_id, "user-1", &registration)
+        .is_err());
+
+    // Authentication.
+    let (ceremony_id, options) = passkeys
+        .start_authentication("user-1", IP, vec![credential.clone()])
+        .unwrap();
+    let assertion = authenticator.do_authentication(ORIGIN, options).unwrap();
+
+    let (owner, credential_id, counter) = passkeys
+        .finish_authentication(&ceremony_id, &assertion)
+        .unwrap();
+    assert_eq!(owner, String::from("user-1"));
+    assert_eq!(credential_id, get_credential_id(&credential));
+    assert!(counter > credential.counter);
+
+    assert!(passkeys
+        .finish_authentication(&ceremony_id, &assertion)
+        .is_err());
+}
+
+#[test]
+fn test_passkey_wrong_origin() {
+    let passkeys = get_passkeys();
+    let mut authenticator = WebauthnAuthenticator::new(U2FSoft::new());
+
+    let (ceremony_id, options) = passkeys
+        .start_registration("user-1", IP, "NextChat")
+        .unwrap();
+    let registration = authenticator
+        .do_registration("https://attacker.localhost:8080", options)
+        .unwrap();
+
+    assert!(passkeys
+        .finish_registration(&ceremony_id, "user-1", &registration)
+        .is_err());
+    assert!(passkeys
+        .start_authentication("user-1", IP, Vec::new())
+        .is_err());
+}
+
+#[test]
+fn test_passkey_ceremonies_per_ip() {
+    let passkeys = get_passkeys();
+
+    for _ in 0..10 {
+        assert!(passkeys
+            .start_registration("user-1", IP, "NextChat")
+            .is_ok());
+    }
+
+    assert!(passkeys
+        .start_registration("user-1", IP, "NextChat")
+        .is_err());
+    assert!(passkeys
+        .start_registration("user-1", "127.0.0.2", "NextChat")
+        .is_ok());
+}
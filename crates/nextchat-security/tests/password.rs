@@ -23,3 +23,21 @@ fn test_app_secret_key_panic() {
     assert!(std::env::var("APP_SECRET_KEY").is_err());
     nextchat_security::encrypt_password("1234").unwrap_or(String::new());
 }
+
+#[test]
+fn test_hash_parameters() {
+    use nextchat_security::HashParameters;
+
+    std::env::set_var("APP_SECRET_KEY", "NextChatORG");
+
+    let hash = nextchat_security::encrypt_password("1234").unwrap();
+    assert_eq!(
+        HashParameters::from_hash(&hash),
+        Some(HashParameters::from_env())
+    );
+    assert!(!nextchat_security::password_needs_rehash(&hash));
+
+    let weaker = "$argon2id$v=19$m=1024,t=1,p=1$c29tZXNhbHQ$aGFzaA";
+    assert!(nextchat_security::password_needs_rehash(weaker));
+    assert!(nextchat_security::password_needs_rehash("invalid hash"));
+}
@@ -0,0 +1,71 @@
+use std::{env, fs, path::PathBuf};
+
+use nextchat_security::{is_breached_password, PasswordPolicy};
+use sha1::{Digest, Sha1};
+
+fn get_policy(breached_passwords_dir: Option<PathBuf>) -> PasswordPolicy {
+    PasswordPolicy {
+        min_length: 8,
+        max_length: 128,
+        breached_passwords_dir,
+    }
+}
+
+#[test]
+fn test_password_length() {
+    let policy = get_policy(None);
+
+    assert_eq!(policy.check("", &[]), Err("You must enter the password."));
+    assert!(policy.check("k#7Pq", &[]).is_err());
+    assert!(policy.check(&"k#7Pq".repeat(26), &[]).is_err());
+
+    // The length is not limited to 40 characters.
+    assert!(policy.check(&"k#7Pq-x9".repeat(10), &[]).is_ok());
+}
+
+#[test]
+fn test_password_strength() {
+    let policy = get_policy(None);
+
+    assert!(policy.check("password", &[]).is_err());
+    assert!(policy.check("abcdefghijkl", &[]).is_err());
+    assert!(policy.check("1111111111", &[]).is_err());
+    assert!(policy.check("monkey12", &[]).is_err());
+    assert!(policy
+        .check("danielsolartech99", &["danielsolartech"])
+        .is_err());
+
+    assert!(policy
+        .check("correct-horse-battery", &["danielsolartech"])
+        .is_ok());
+    assert!(policy.check("Tr0ub4dor&3x", &[]).is_ok());
+}
+
+#[test]
+fn test_breached_password() {
+    let directory: PathBuf = env::temp_dir().join("nextchat-breached-passwords");
+    fs::create_dir_all(&directory).unwrap();
+
+    let hash: String = format!("{:X}", Sha1::digest(b"correct-horse-battery"));
+    let (prefix, suffix) = hash.split_at(5);
+    fs::write(
+        directory.join(format!("{}.txt", prefix)),
+        format!(
+            "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n{}:23174\r\n",
+            suffix
+        ),
+    )
+    .unwrap();
+
+    assert!(is_breached_password("correct-horse-battery", &directory));
+    assert!(!is_breached_password(
+        "correct-horse-battery-staple",
+        &directory
+    ));
+
+    let policy = get_policy(Some(directory));
+    assert_eq!(
+        policy.check("correct-horse-battery", &[]),
+        Err("The password appeared in a data breach, choose another password.")
+    );
+}
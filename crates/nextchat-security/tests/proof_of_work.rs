@@ -0,0 +1,47 @@
+use nextchat_security::{solve_proof_of_work, verify_proof_of_work, ProofOfWork};
+
+#[test]
+fn test_proof_of_work_solution() {
+    let solution: String = solve_proof_of_work("NextChat", 12);
+
+    assert!(verify_proof_of_work("NextChat", &solution, 12));
+    assert!(verify_proof_of_work("NextChat", &solution, 0));
+
+    // The solution is bound to the challenge.
+    assert!(!verify_proof_of_work("NextChatORG", &solution, 24));
+    assert!(!verify_proof_of_work("NextChat", "", 24));
+}
+
+#[test]
+fn test_adaptive_difficulty() {
+    let proof_of_work = ProofOfWork {
+        difficulty: 16,
+        max_difficulty: 20,
+        signup_threshold: 10,
+    };
+
+    assert_eq!(proof_of_work.get_difficulty(0), 16);
+    assert_eq!(proof_of_work.get_difficulty(10), 16);
+    assert_eq!(proof_of_work.get_difficulty(11), 18);
+    assert_eq!(proof_of_work.get_difficulty(21), 20);
+    assert_eq!(proof_of_work.get_difficulty(u64::MAX), 20);
+
+    // The difficulty is not raised without threshold.
+    let fixed = ProofOfWork {
+        signup_threshold: 0,
+        ..proof_of_work
+    };
+    assert_eq!(fixed.get_difficulty(1_000), 16);
+}
+
+#[test]
+fn test_disabled_proof_of_work() {
+    let disabled = ProofOfWork {
+        difficulty: 0,
+        max_difficulty: 20,
+        signup_threshold: 10,
+    };
+
+    assert!(!disabled.is_enabled());
+    assert_eq!(disabled.get_difficulty(1_000), 0);
+}
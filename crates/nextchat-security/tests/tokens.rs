@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+}
+
+#[test]
+fn test_token_signature() {
+    std::env::set_var("APP_SECRET_KEY", "NextChatORG");
+
+    let token = nextchat_security::encode_token(&Claims {
+        sub: String::from("NextChat"),
+        exp: nextchat_security::get_timestamp() + 60,
+    });
+    assert!(token.is_ok());
+
+    let token = token.unwrap();
+
+    let claims = nextchat_security::decode_token::<Claims>(&token);
+    assert!(claims.is_ok());
+    assert_eq!(claims.unwrap().sub, String::from("NextChat"));
+
+    // Change a character of the signature.
+    let index = token.len() - 5;
+    let replacement = if &token[index..index + 1] == "A" {
+        "B"
+    } else {
+        "A"
+    };
+    let tampered = format!("{}{}{}", &token[..index], replacement, &token[index + 1..]);
+    assert!(nextchat_security::decode_token::<Claims>(&tampered).is_err());
+}
+
+#[test]
+fn test_token_expiration() {
+    std::env::set_var("APP_SECRET_KEY", "NextChatORG");
+
+    let token = nextchat_security::encode_token(&Claims {
+        sub: String::from("NextChat"),
+        exp: nextchat_security::get_timestamp() - 120,
+    })
+    .unwrap();
+
+    assert!(nextchat_security::decode_token::<Claims>(&token).is_err());
+}
+
+#[test]
+fn test_random_token_hash() {
+    let token = nextchat_security::generate_random_token();
+    let other_token = nextchat_security::generate_random_token();
+    assert_ne!(token, other_token);
+
+    assert_eq!(
+        nextchat_security::hash_token(&token),
+        nextchat_security::hash_token(&token)
+    );
+    assert_ne!(
+        nextchat_security::hash_token(&token),
+        nextchat_security::hash_token(&other_token)
+    );
+}
+
+#[test]
+fn test_recovery_code() {
+    let code = nextchat_security::generate_recovery_code();
+    assert_eq!(code.split('-').count(), 4);
+    assert_ne!(code, nextchat_security::generate_recovery_code());
+
+    assert_eq!(
+        nextchat_security::normalize_recovery_code(" ABCD-efgh-2345-6712 "),
+        String::from("abcdefgh23456712")
+    );
+}
+
+#[test]
+fn test_format_recovery_code() {
+    let code = nextchat_security::generate_recovery_code();
+    assert_eq!(nextchat_security::format_recovery_code(&code), code);
+
+    assert_eq!(
+        nextchat_security::format_recovery_code(" ABCD efgh-2345_6712 "),
+        String::from("abcd-efgh-2345-6712")
+    );
+    assert_eq!(
+        nextchat_security::format_recovery_code("abcdefgh23"),
+        String::from("abcd-efgh-23")
+    );
+    assert_eq!(
+        nextchat_security::format_recovery_code(" - "),
+        String::new()
+    );
+}
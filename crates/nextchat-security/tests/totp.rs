@@ -0,0 +1,72 @@
+/// RFC 6238 secret (`12345678901234567890`) encoded in base32.
+const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn test_totp_codes() {
+    // RFC 6238 SHA1 test vectors truncated to 6 digits.
+    for (timestamp, code) in [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            nextchat_security::get_totp_code(SECRET, *timestamp),
+            Ok(String::from(*code))
+        );
+    }
+
+    assert!(nextchat_security::get_totp_code("not base32!", 59).is_err());
+}
+
+#[test]
+fn test_totp_verification() {
+    let secret = nextchat_security::generate_totp_secret();
+    assert_eq!(secret.len(), 32);
+
+    let timestamp = nextchat_security::get_timestamp();
+    let code = nextchat_security::get_totp_code(&secret, timestamp).unwrap();
+    assert_eq!(
+        nextchat_security::verify_totp_code(&secret, &code, timestamp),
+        Some(timestamp / 30)
+    );
+
+    // Clock drift of one step.
+    assert!(nextchat_security::verify_totp_code(&secret, &code, timestamp + 30).is_some());
+    assert!(nextchat_security::verify_totp_code(&secret, &code, timestamp + 90).is_none());
+    assert!(nextchat_security::verify_totp_code(&secret, "12345", timestamp).is_none());
+}
+
+#[test]
+fn test_totp_secret_encryption() {
+    std::env::set_var("APP_SECRET_KEY", "NextChatORG");
+
+    let encrypted = nextchat_security::encrypt_secret(SECRET).unwrap();
+    assert!(!encrypted.contains(SECRET));
+    assert_ne!(
+        encrypted,
+        nextchat_security::encrypt_secret(SECRET).unwrap()
+    );
+    assert_eq!(
+        nextchat_security::decrypt_secret(&encrypted),
+        Ok(String::from(SECRET))
+    );
+
+    // Change a character of the ciphertext.
+    let index = encrypted.len() - 3;
+    let replacement = if &encrypted[index..index + 1] == "A" {
+        "B"
+    } else {
+        "A"
+    };
+    let tampered = format!(
+        "{}{}{}",
+        &encrypted[..index],
+        replacement,
+        &encrypted[index + 1..]
+    );
+    assert!(nextchat_security::decrypt_secret(&tampered).is_err());
+}
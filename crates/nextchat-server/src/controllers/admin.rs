@@ -0,0 +1,214 @@
+//! NextChat Server admin controller module.
+//!
+//! This module contains the routes of the `/admin` path.
+//!
+//! # Routes
+//! `/admin/users`                  -> get_users
+//! `/admin/users/:user_id/role`    -> change_role
+//! `/admin/users/:user_id/ban`     -> ban
+//! `/admin/users/:user_id/ban`     -> unban
+//! `/admin/users/:user_id/logout`  -> logout
+//! `/admin/connections`            -> get_connections
+//! `/admin/announcements`          -> announcement
+//! `/admin/audit-log`              -> get_audit_log
+//!
+//! All routes require the `Authorization: Bearer {token}` header and a permission
+//! of the user role.
+//!
+//! See `/src/services/admin.rs` for more information about the routes handlers.
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{
+        admin::{AdminUsersQuery, AnnouncementBody, BanBody},
+        audit::AuditLogQuery,
+        roles::{ChangeRoleBody, Permission},
+    },
+    Client, Uuid,
+};
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+use super::{with_client, with_ip_address, with_permission, with_storage};
+
+/// The prefix of all routes of this module.
+fn prefix() -> BoxedFilter<()> {
+    warp::path("admin").boxed()
+}
+
+/// `/admin/users` route declaration.
+///
+/// # Permission
+/// `ViewUsers`
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+/// - `?search={text}`
+fn get_users(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("users"))
+        .and(with_permission(client.clone(), Permission::ViewUsers))
+        .and(warp::query::<AdminUsersQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::admin::get_users_handler)
+}
+
+/// `/admin/users/:user_id/role` route declaration.
+///
+/// # Permission
+/// `ManageRoles`
+///
+/// # Body
+/// ```json
+/// {
+///     "role": "moderator"
+/// }
+/// ```
+fn change_role(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::put()
+        .and(prefix())
+        .and(warp::path!("users" / Uuid / "role"))
+        .and(with_permission(client.clone(), Permission::ManageRoles))
+        .and(warp::body::json::<ChangeRoleBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::admin::change_role_handler)
+}
+
+/// `/admin/users/:user_id/ban` route declaration.
+///
+/// # Permission
+/// `ManageUsers`
+///
+/// # Body
+/// ```json
+/// {
+///     "reason": "Spam"
+/// }
+/// ```
+fn ban(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("users" / Uuid / "ban"))
+        .and(with_permission(client.clone(), Permission::ManageUsers))
+        .and(warp::body::json::<BanBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::admin::ban_handler)
+}
+
+/// `/admin/users/:user_id/ban` route declaration.
+///
+/// # Permission
+/// `ManageUsers`
+fn unban(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(prefix())
+        .and(warp::path!("users" / Uuid / "ban"))
+        .and(with_permission(client.clone(), Permission::ManageUsers))
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::admin::unban_handler)
+}
+
+/// `/admin/users/:user_id/logout` route declaration.
+///
+/// # Permission
+/// `ManageUsers`
+fn logout(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("users" / Uuid / "logout"))
+        .and(with_permission(client.clone(), Permission::ManageUsers))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::admin::logout_handler)
+}
+
+/// `/admin/connections` route declaration.
+///
+/// # Permission
+/// `ViewUsers`
+fn get_connections(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("connections"))
+        .and(with_permission(client.clone(), Permission::ViewUsers))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::admin::get_connections_handler)
+}
+
+/// `/admin/announcements` route declaration.
+///
+/// # Permission
+/// `SendAnnouncements`
+///
+/// # Body
+/// ```json
+/// {
+///     "message": "The server will restart in 5 minutes."
+/// }
+/// ```
+fn announcement(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("announcements"))
+        .and(with_permission(
+            client.clone(),
+            Permission::SendAnnouncements,
+        ))
+        .and(warp::body::json::<AnnouncementBody>())
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::admin::announcement_handler)
+}
+
+/// `/admin/audit-log` route declaration.
+///
+/// # Permission
+/// `ViewAuditLog`
+///
+/// # Query
+/// - `?user_id={user_id}`
+/// - `?event={event}`
+/// - `?from={timestamp}`
+/// - `?to={timestamp}`
+/// - `?take={number}`
+/// - `?skip={number}`
+fn get_audit_log(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("audit-log"))
+        .and(with_permission(client.clone(), Permission::ViewAuditLog))
+        .and(warp::query::<AuditLogQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::admin::get_audit_log_handler)
+}
+
+/// Combine all `/admin` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    get_users(client)
+        .or(change_role(client))
+        .or(ban(client, storage))
+        .or(unban(client))
+        .or(logout(client, storage))
+        .or(get_connections(client, storage))
+        .or(announcement(client, storage))
+        .or(get_audit_log(client))
+}
@@ -0,0 +1,108 @@
+//! NextChat Server authentication controller module.
+//!
+//! This module contains the routes of the `/auth` path.
+//!
+//! # Routes
+//! `/auth/refresh`                 -> refresh
+//! `/auth/logout`                  -> logout
+//! `/auth/logout-all`              -> logout_all
+//! `/auth/sessions`                -> get_sessions
+//! `/auth/sessions/:session_id`    -> delete_session
+//!
+//! See `/src/services/authentication.rs` for more information about the routes handlers.
+
+use nextchat_communication::StorageType;
+use nextchat_database::{models::sessions::RefreshBody, Client, Uuid};
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client, with_storage};
+
+/// The prefix of all routes of this module.
+fn prefix() -> BoxedFilter<()> {
+    warp::path("auth").boxed()
+}
+
+/// `/auth/refresh` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "refresh_token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+fn refresh(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("refresh"))
+        .and(warp::body::json::<RefreshBody>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::authentication::refresh_handler)
+}
+
+/// `/auth/logout` route declaration.
+fn logout(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("logout"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::authentication::logout_handler)
+}
+
+/// `/auth/logout-all` route declaration.
+fn logout_all(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("logout-all"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::authentication::logout_all_handler)
+}
+
+/// `/auth/sessions` route declaration.
+fn get_sessions(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("sessions"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::authentication::get_sessions_handler)
+}
+
+/// `/auth/sessions/:session_id` route declaration.
+fn delete_session(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(prefix())
+        .and(warp::path!("sessions" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::authentication::delete_session_handler)
+}
+
+/// Combine all `/auth` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    refresh(client)
+        .or(logout(client, storage))
+        .or(logout_all(client, storage))
+        .or(get_sessions(client, storage))
+        .or(delete_session(client, storage))
+}
@@ -0,0 +1,54 @@
+//! NextChat Server blocks controller module.
+//!
+//! This module contains the routes of the `/users/blocks` path.
+//!
+//! # Routes
+//! `/users/blocks`             -> get_blocks
+//! `/users/blocks/:user_id`    -> block
+//! `/users/blocks/:user_id`    -> unblock
+//!
+//! All routes require the `Authorization: Bearer {token}` header.
+//!
+//! See `/src/services/blocks.rs` for more information about the routes handlers.
+
+use nextchat_database::{models::blocks::BlocksQuery, Client, Uuid};
+use warp::{Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client};
+
+/// `/users/blocks` route declaration.
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+fn get_blocks(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("users" / "blocks"))
+        .and(with_authentication(client.clone()))
+        .and(warp::query::<BlocksQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::blocks::get_blocks_handler)
+}
+
+/// `/users/blocks/:user_id` route declaration.
+fn block(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "blocks" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::blocks::block_handler)
+}
+
+/// `/users/blocks/:user_id` route declaration.
+fn unblock(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(warp::path!("users" / "blocks" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::blocks::unblock_handler)
+}
+
+/// Combine all `/users/blocks` routes to export.
+pub fn routes(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    get_blocks(client).or(block(client)).or(unblock(client))
+}
@@ -1,32 +1,51 @@
 //! NextChat Server connection controller module.
+//!
+//! This module contains the WebSocket routes.
+//!
+//! # Routes
+//! `/connection?token={access_token}`          -> connection
+//! `/connection/link?device_name={name}`       -> link
+//!
+//! See `/src/services/connection.rs` for more information about the connections.
 
 use nextchat_communication::StorageType;
-use nextchat_database::{Client, Uuid};
+use nextchat_database::Client;
 use serde::Deserialize;
 use warp::{ws::Ws, Filter, Rejection, Reply};
 
-use super::{with_client, with_storage};
+use super::{with_client, with_ip_address, with_storage, with_user_agent};
+use crate::services::authentication::Authentication;
 
-pub fn routes(
+/// `/connection?token={access_token}` route declaration.
+fn connection(
     client: &Client,
     storage: &StorageType,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     #[derive(Deserialize)]
     struct Query {
-        pub user_id: Uuid,
+        pub token: String,
     }
 
     warp::ws()
-        .and(warp::path("connection"))
-        .and(warp::query::<Query>())
+        .and(warp::path!("connection"))
+        .and(
+            warp::query::<Query>()
+                .and(with_client(client.clone()))
+                .and_then(|query: Query, client: Client| async move {
+                    crate::services::authentication::authenticate(&client, &query.token).await
+                }),
+        )
         .and(with_client(client.clone()))
         .and(with_storage(storage.clone()))
         .map(
-            |websocket: Ws, query: Query, client: Client, storage: StorageType| {
+            |websocket: Ws,
+             authentication: Authentication,
+             client: Client,
+             storage: StorageType| {
                 websocket.on_upgrade(move |socket| {
                     crate::services::connection::on_new_connection(
                         socket,
-                        query.user_id,
+                        authentication,
                         client,
                         storage,
                     )
@@ -34,3 +53,52 @@ pub fn routes(
             },
         )
 }
+
+/// `/connection/link?device_name={name}` route declaration.
+///
+/// Unauthenticated connection of a device waiting to be linked by an authenticated
+/// connection, see `/src/services/connection.rs`.
+fn link(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    #[derive(Deserialize)]
+    struct Query {
+        pub device_name: Option<String>,
+    }
+
+    warp::ws()
+        .and(warp::path!("connection" / "link"))
+        .and(warp::query::<Query>())
+        .and(with_ip_address())
+        .and(with_user_agent())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .map(
+            |websocket: Ws,
+             query: Query,
+             ip: String,
+             user_agent: String,
+             client: Client,
+             storage: StorageType| {
+                websocket.on_upgrade(move |socket| {
+                    crate::services::connection::on_new_link_connection(
+                        socket,
+                        query.device_name,
+                        ip,
+                        user_agent,
+                        client,
+                        storage,
+                    )
+                })
+            },
+        )
+}
+
+/// Combine all `/connection` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    connection(client, storage).or(link(client, storage))
+}
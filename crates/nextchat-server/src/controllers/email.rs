@@ -0,0 +1,105 @@
+//! NextChat Server email controller module.
+//!
+//! This module contains the routes of the `/users/email` path.
+//!
+//! # Routes
+//! `/users/email`          -> get_email
+//! `/users/email`          -> change_email
+//! `/users/email/resend`   -> resend
+//! `/users/email/verify`   -> verify
+//!
+//! All routes except `/users/email/verify` require the `Authorization: Bearer {token}`
+//! header.
+//!
+//! See `/src/services/email.rs` for more information about the routes handlers.
+
+use std::convert::Infallible;
+
+use nextchat_communication::StorageType;
+use nextchat_database::{models::email::*, Client};
+use nextchat_mailer::MailerType;
+use warp::{Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client, with_ip_address, with_storage};
+
+/// This function helps to add a copy of the mailer to a warp path.
+fn with_mailer(
+    mailer: MailerType,
+) -> impl Filter<Extract = (MailerType,), Error = Infallible> + Clone {
+    warp::any().map(move || mailer.clone())
+}
+
+/// `/users/email` route declaration.
+fn get_email(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("users" / "email"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::email::get_email_handler)
+}
+
+/// `/users/email` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "email": "contact@nextchat.org",
+///     "password": "12345678"
+/// }
+/// ```
+fn change_email(
+    client: &Client,
+    storage: &StorageType,
+    mailer: &MailerType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::put()
+        .and(warp::path!("users" / "email"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<ChangeEmailBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and(with_mailer(mailer.clone()))
+        .and_then(crate::services::email::change_email_handler)
+}
+
+/// `/users/email/resend` route declaration.
+fn resend(
+    client: &Client,
+    mailer: &MailerType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "email" / "resend"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_mailer(mailer.clone()))
+        .and_then(crate::services::email::resend_handler)
+}
+
+/// `/users/email/verify` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+fn verify(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "email" / "verify"))
+        .and(warp::body::json::<VerifyEmailBody>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::email::verify_handler)
+}
+
+/// Combine all `/users/email` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+    mailer: &MailerType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    get_email(client)
+        .or(change_email(client, storage, mailer))
+        .or(resend(client, mailer))
+        .or(verify(client))
+}
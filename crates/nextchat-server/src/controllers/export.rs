@@ -0,0 +1,37 @@
+//! NextChat Server export controller module.
+//!
+//! This module contains the personal data export route.
+//!
+//! # Routes
+//! `/users/export` -> export
+//!
+//! The route requires the `Authorization: Bearer {token}` header.
+//!
+//! See `/src/services/export.rs` for more information about the route handler.
+
+use nextchat_communication::StorageType;
+use nextchat_database::Client;
+use warp::{Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client, with_storage};
+
+/// `/users/export` route declaration.
+fn export(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("users" / "export"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::export::export_handler)
+}
+
+/// Combine all `/users/export` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    export(client, storage)
+}
@@ -3,14 +3,26 @@
 //! This module contains the routes of the `/friends` path.
 //!
 //! # Routes
-//! `/friends/:user_one/:user_two` -> are_friends
+//! `/friends/:user_one/:user_two`          -> are_friends
+//! `/friends/get-of/:user_id`              -> get_of
+//! `/friends/requests/incoming`            -> incoming_requests
+//! `/friends/requests/outgoing`            -> outgoing_requests
+//! `/friends/suggestions`                  -> suggestions
+//! `/friends/requests/:user_id`            -> send_request
+//! `/friends/requests/:user_id/accept`     -> accept_request
+//! `/friends/requests/:user_id/decline`    -> decline_request
+//! `/friends/requests/:user_id`            -> cancel_request
+//! `/friends/:user_id`                     -> unfriend
+//!
+//! All routes require the `Authorization: Bearer {token}` header.
 //!
 //! See `/src/services/friends.rs` for more information about the routes handlers.
 
-use nextchat_database::{Client, Uuid};
+use nextchat_communication::StorageType;
+use nextchat_database::{models::friends::FriendsQuery, Client, Uuid};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
-use super::with_client;
+use super::{with_authentication, with_client, with_storage};
 
 /// The prefix of all routes of this module.
 fn prefix() -> BoxedFilter<()> {
@@ -22,11 +34,148 @@ fn are_friends(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rej
     warp::get()
         .and(prefix())
         .and(warp::path!(Uuid / Uuid))
+        .and(with_authentication(client.clone()))
         .and(with_client(client.clone()))
         .and_then(crate::services::friends::are_friends_handler)
 }
 
+/// `/friends/get-of/:user_id` route declaration.
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+fn get_of(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("get-of" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(warp::query::<FriendsQuery>())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::friends::get_of_handler)
+}
+
+/// `/friends/requests/incoming` route declaration.
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+fn incoming_requests(
+    client: &Client,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("requests" / "incoming"))
+        .and(with_authentication(client.clone()))
+        .and(warp::query::<FriendsQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::incoming_requests_handler)
+}
+
+/// `/friends/requests/outgoing` route declaration.
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+fn outgoing_requests(
+    client: &Client,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("requests" / "outgoing"))
+        .and(with_authentication(client.clone()))
+        .and(warp::query::<FriendsQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::outgoing_requests_handler)
+}
+
+/// `/friends/suggestions` route declaration.
+///
+/// # Query
+/// - `?take={number}`
+/// - `?skip={number}`
+fn suggestions(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("suggestions"))
+        .and(with_authentication(client.clone()))
+        .and(warp::query::<FriendsQuery>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::suggestions_handler)
+}
+
+/// `/friends/requests/:user_id` route declaration.
+fn send_request(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("requests" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::friends::send_request_handler)
+}
+
+/// `/friends/requests/:user_id/accept` route declaration.
+fn accept_request(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("requests" / Uuid / "accept"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::accept_request_handler)
+}
+
+/// `/friends/requests/:user_id/decline` route declaration.
+fn decline_request(
+    client: &Client,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path!("requests" / Uuid / "decline"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::decline_request_handler)
+}
+
+/// `/friends/requests/:user_id` route declaration.
+fn cancel_request(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(prefix())
+        .and(warp::path!("requests" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::cancel_request_handler)
+}
+
+/// `/friends/:user_id` route declaration.
+fn unfriend(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(prefix())
+        .and(warp::path!(Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::friends::unfriend_handler)
+}
+
 /// Combine all `/friends` routes to export.
-pub fn routes(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     are_friends(client)
+        .or(get_of(client, storage))
+        .or(incoming_requests(client))
+        .or(outgoing_requests(client))
+        .or(suggestions(client))
+        .or(send_request(client, storage))
+        .or(accept_request(client))
+        .or(decline_request(client))
+        .or(cancel_request(client))
+        .or(unfriend(client))
 }
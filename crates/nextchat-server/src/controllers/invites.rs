@@ -0,0 +1,60 @@
+//! NextChat Server invites controller module.
+//!
+//! This module contains the routes of the `/users/invites` path.
+//!
+//! # Routes
+//! `/users/invites`        -> get_invites
+//! `/users/invites`        -> create_invite
+//! `/users/invites/:code`  -> delete_invite
+//!
+//! All routes require the `Authorization: Bearer {token}` header.
+//!
+//! See `/src/services/invites.rs` for more information about the routes handlers.
+
+use nextchat_database::{models::invites::*, Client};
+use warp::{Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client};
+
+/// `/users/invites` route declaration.
+fn get_invites(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("users" / "invites"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::invites::get_invites_handler)
+}
+
+/// `/users/invites` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "max_uses": 1,
+///     "expires_in_days": 7
+/// }
+/// ```
+fn create_invite(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "invites"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<CreateInviteBody>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::invites::create_invite_handler)
+}
+
+/// `/users/invites/:code` route declaration.
+fn delete_invite(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(warp::path!("users" / "invites" / String))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::invites::delete_invite_handler)
+}
+
+/// Combine all `/users/invites` routes to export.
+pub fn routes(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    get_invites(client)
+        .or(create_invite(client))
+        .or(delete_invite(client))
+}
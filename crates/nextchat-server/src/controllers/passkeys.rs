@@ -0,0 +1,163 @@
+//! NextChat Server passkeys controller module.
+//!
+//! This module contains the routes of the `/users/passkeys` path and the passkey
+//! sign in ceremony.
+//!
+//! # Routes
+//! `/users/passkeys`                   -> get_passkeys
+//! `/users/passkeys/:passkey_id`       -> delete_passkey
+//! `/users/passkeys/register/start`    -> register_start
+//! `/users/passkeys/register/finish`   -> register_finish
+//! `/users/signin/passkey/start`       -> signin_start
+//! `/users/signin/passkey/finish`      -> signin_finish
+//!
+//! The `/users/passkeys` routes require the `Authorization: Bearer {token}` header.
+//!
+//! See `/src/services/passkeys.rs` for more information about the routes handlers.
+
+use std::convert::Infallible;
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{passkeys::*, users::PasswordConfirmationBody},
+    Client, Uuid,
+};
+use warp::{Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client, with_ip_address, with_storage, with_user_agent};
+use crate::services::passkeys::PasskeysType;
+
+/// This function helps to add a copy of the passkeys ceremonies to a warp path.
+fn with_passkeys(
+    passkeys: PasskeysType,
+) -> impl Filter<Extract = (PasskeysType,), Error = Infallible> + Clone {
+    warp::any().map(move || passkeys.clone())
+}
+
+/// `/users/passkeys` route declaration.
+fn get_passkeys(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("users" / "passkeys"))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::passkeys::get_passkeys_handler)
+}
+
+/// `/users/passkeys/:passkey_id` route declaration.
+fn delete_passkey(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(warp::path!("users" / "passkeys" / Uuid))
+        .and(with_authentication(client.clone()))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::passkeys::delete_passkey_handler)
+}
+
+/// `/users/passkeys/register/start` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+fn register_start(
+    client: &Client,
+    storage: &StorageType,
+    passkeys: &PasskeysType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "passkeys" / "register" / "start"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<PasswordConfirmationBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and(with_passkeys(passkeys.clone()))
+        .and_then(crate::services::passkeys::register_start_handler)
+}
+
+/// `/users/passkeys/register/finish` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "name": "YubiKey",
+///     "credential": { "id": "...", "rawId": "...", "response": { ... }, "type": "public-key" }
+/// }
+/// ```
+fn register_finish(
+    client: &Client,
+    passkeys: &PasskeysType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "passkeys" / "register" / "finish"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<FinishPasskeyRegistrationBody>())
+        .and(with_client(client.clone()))
+        .and(with_passkeys(passkeys.clone()))
+        .and_then(crate::services::passkeys::register_finish_handler)
+}
+
+/// `/users/signin/passkey/start` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "username": "NextChat"
+/// }
+/// ```
+fn signin_start(
+    client: &Client,
+    storage: &StorageType,
+    passkeys: &PasskeysType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "signin" / "passkey" / "start"))
+        .and(warp::body::json::<StartPasskeySignInBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and(with_passkeys(passkeys.clone()))
+        .and_then(crate::services::passkeys::signin_start_handler)
+}
+
+/// `/users/signin/passkey/finish` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "credential": { "id": "...", "rawId": "...", "response": { ... }, "type": "public-key" },
+///     "device_name": "NextChat Desktop"
+/// }
+/// ```
+fn signin_finish(
+    client: &Client,
+    storage: &StorageType,
+    passkeys: &PasskeysType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "signin" / "passkey" / "finish"))
+        .and(warp::body::json::<FinishPasskeySignInBody>())
+        .and(with_ip_address())
+        .and(with_user_agent())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and(with_passkeys(passkeys.clone()))
+        .and_then(crate::services::passkeys::signin_finish_handler)
+}
+
+/// Combine all `/users/passkeys` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+    passkeys: &PasskeysType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    get_passkeys(client)
+        .or(delete_passkey(client))
+        .or(register_start(client, storage, passkeys))
+        .or(register_finish(client, passkeys))
+        .or(signin_start(client, storage, passkeys))
+        .or(signin_finish(client, storage, passkeys))
+}
@@ -0,0 +1,128 @@
+//! NextChat Server two-factor authentication controller module.
+//!
+//! This module contains the routes of the `/users/2fa` path and the second step
+//! of the sign in.
+//!
+//! # Routes
+//! `/users/2fa/enroll`     -> enroll
+//! `/users/2fa/confirm`    -> confirm
+//! `/users/2fa/disable`    -> disable
+//! `/users/signin/2fa`     -> second_factor
+//!
+//! All routes except `/users/signin/2fa` require the `Authorization: Bearer {token}` header.
+//!
+//! See `/src/services/two_factor.rs` for more information about the routes handlers.
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{two_factor::*, users::PasswordConfirmationBody},
+    Client,
+};
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+use super::{with_authentication, with_client, with_ip_address, with_storage, with_user_agent};
+
+/// The prefix of all routes of this module, except `/users/signin/2fa`.
+fn prefix() -> BoxedFilter<()> {
+    warp::path("users").and(warp::path("2fa")).boxed()
+}
+
+/// `/users/2fa/enroll` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+fn enroll(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("enroll"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<PasswordConfirmationBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::two_factor::enroll_handler)
+}
+
+/// `/users/2fa/confirm` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "code": "287082"
+/// }
+/// ```
+fn confirm(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("confirm"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<TotpCodeBody>())
+        .and(with_client(client.clone()))
+        .and_then(crate::services::two_factor::confirm_handler)
+}
+
+/// `/users/2fa/disable` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "password": "12345678",
+///     "code": "287082"
+/// }
+/// ```
+fn disable(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("disable"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<DisableTotpBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::two_factor::disable_handler)
+}
+
+/// `/users/signin/2fa` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "code": "287082",
+///     "device_name": "NextChat Desktop"
+/// }
+/// ```
+fn second_factor(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("users" / "signin" / "2fa"))
+        .and(warp::body::json::<SecondFactorBody>())
+        .and(with_ip_address())
+        .and(with_user_agent())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::two_factor::second_factor_handler)
+}
+
+/// Combine all `/users/2fa` routes to export.
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    enroll(client, storage)
+        .or(confirm(client))
+        .or(disable(client, storage))
+        .or(second_factor(client, storage))
+}
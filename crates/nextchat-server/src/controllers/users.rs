@@ -8,14 +8,23 @@
 //! `/users/find?id={user_id}`          -> find
 //! `/users/find?username={username}`   -> find
 //! `/users/signup`                     -> signup
+//! `/users/signup/challenge`           -> signup_challenge
 //! `/users/signin`                     -> signin
+//! `/users/password`                   -> change_password
+//! `/users/recovery-codes`             -> recovery_codes
+//! `/users/recover`                    -> recover
+//! `/users/account`                    -> delete_account
+//!
+//! All routes except `/users/signup`, `/users/signup/challenge`, `/users/signin` and
+//! `/users/recover` require the `Authorization: Bearer {token}` header.
 //!
 //! See `/src/services/users.rs` for more information about the routes handlers.
 
+use nextchat_communication::StorageType;
 use nextchat_database::{models::users::*, Client};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
-use super::with_client;
+use super::{with_authentication, with_client, with_ip_address, with_storage, with_user_agent};
 
 /// The prefix of all routes of this module.
 fn prefix() -> BoxedFilter<()> {
@@ -31,6 +40,7 @@ fn get_all(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejecti
     warp::get()
         .and(prefix())
         .and(warp::path("all"))
+        .and(with_authentication(client.clone()))
         .and(warp::query::<GetAllQuery>())
         .and(with_client(client.clone()))
         .and_then(crate::services::users::get_all_handler)
@@ -45,6 +55,7 @@ fn search(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejectio
     warp::get()
         .and(prefix())
         .and(warp::path!("search" / String))
+        .and(with_authentication(client.clone()))
         .and(warp::query::<SearchQuery>())
         .and(with_client(client.clone()))
         .and_then(crate::services::users::search_handler)
@@ -59,6 +70,7 @@ fn find(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection>
     warp::get()
         .and(prefix())
         .and(warp::path("find"))
+        .and(with_authentication(client.clone()))
         .and(warp::query::<FindQuery>())
         .and(with_client(client.clone()))
         .and_then(crate::services::users::find_handler)
@@ -70,7 +82,11 @@ fn find(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection>
 /// ```json
 /// {
 ///     "username": "NextChat",
-///     "password": "1234"
+///     "password": "1234",
+///     "device_name": "NextChat Desktop",
+///     "invite_code": "k2vd-q7xm-a4np-3hse",
+///     "pow_challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "pow_solution": "1048576"
 /// }
 /// ```
 fn signup(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
@@ -78,33 +94,155 @@ fn signup(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejectio
         .and(prefix())
         .and(warp::path("signup"))
         .and(warp::body::json::<SignUpAndSignInBody>())
+        .and(with_ip_address())
+        .and(with_user_agent())
         .and(with_client(client.clone()))
         .and_then(crate::services::users::signup_handler)
 }
 
+/// `/users/signup/challenge` route declaration.
+fn signup_challenge(
+    client: &Client,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::get()
+        .and(prefix())
+        .and(warp::path!("signup" / "challenge"))
+        .and(with_client(client.clone()))
+        .and_then(crate::services::proof_of_work::signup_challenge_handler)
+}
+
 /// `/users/signin` route declaration.
 ///
 /// # Body
 /// ```json
 /// {
 ///     "username": "NextChat",
-///     "password": "1234"
+///     "password": "1234",
+///     "device_name": "NextChat Desktop"
 /// }
 /// ```
-fn signin(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+fn signin(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::post()
         .and(prefix())
-        .and(warp::path("signin"))
+        .and(warp::path!("signin"))
         .and(warp::body::json::<SignUpAndSignInBody>())
+        .and(with_ip_address())
+        .and(with_user_agent())
         .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
         .and_then(crate::services::users::signin_handler)
 }
 
+/// `/users/password` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "old_password": "12345678",
+///     "new_password": "87654321"
+/// }
+/// ```
+fn change_password(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("password"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<ChangePasswordBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::users::change_password_handler)
+}
+
+/// `/users/recovery-codes` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+fn recovery_codes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("recovery-codes"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<PasswordConfirmationBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::users::recovery_codes_handler)
+}
+
+/// `/users/recover` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "username": "NextChat",
+///     "recovery_code": "k2vd-q7xm-a4np-3hse",
+///     "new_password": "87654321"
+/// }
+/// ```
+fn recover(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::post()
+        .and(prefix())
+        .and(warp::path("recover"))
+        .and(warp::body::json::<RecoverBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::users::recover_handler)
+}
+
+/// `/users/account` route declaration.
+///
+/// # Body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+fn delete_account(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::delete()
+        .and(prefix())
+        .and(warp::path("account"))
+        .and(with_authentication(client.clone()))
+        .and(warp::body::json::<PasswordConfirmationBody>())
+        .and(with_ip_address())
+        .and(with_client(client.clone()))
+        .and(with_storage(storage.clone()))
+        .and_then(crate::services::users::delete_account_handler)
+}
+
 /// Combine all `/users` routes to export.
-pub fn routes(client: &Client) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+pub fn routes(
+    client: &Client,
+    storage: &StorageType,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     get_all(client)
         .or(search(client))
         .or(find(client))
         .or(signup(client))
-        .or(signin(client))
+        .or(signup_challenge(client))
+        .or(signin(client, storage))
+        .or(change_password(client, storage))
+        .or(recovery_codes(client, storage))
+        .or(recover(client, storage))
+        .or(delete_account(client, storage))
 }
@@ -1,16 +1,61 @@
 //! NextChat Server library.
 
+use std::{sync::Arc, time::Duration};
+
+use nextchat_communication::{Storage, StorageType};
 use nextchat_database::Client;
+use nextchat_security::Passkeys;
 
 mod controllers;
 mod response;
 mod services;
 
-pub use controllers::{with_client, with_storage};
+pub use controllers::{
+    authenticated, with_authentication, with_client, with_ip_address, with_permission,
+    with_storage, with_user_agent,
+};
 pub use response::{Error, Response};
+pub use services::authentication::Authentication;
+
+/// Interval to delete the accounts whose deletion grace period has ended, the
+/// expired proof of work challenges, the expired passkey ceremonies, the old failed
+/// signin attempts and the expired pending links (1 hour).
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub async fn run(client: &Client, host: [u8; 4], port: u16) {
-    warp::serve(controllers::routes(client))
-        .run((host, port))
-        .await;
+    let storage: StorageType = Storage::default();
+    storage
+        .write()
+        .await
+        .set_friend_request_handler(Arc::new(services::friends::FriendRequests));
+    let passkeys: services::passkeys::PasskeysType = Arc::new(Passkeys::from_env());
+
+    // Fail on startup instead of on the first signin if the password hashing is misconfigured.
+    nextchat_security::init_dummy_password_hash();
+
+    services::admin::grant_initial_admins(client).await;
+
+    // Delete the accounts scheduled for deletion and the expired challenges in the background.
+    let purge_client = client.clone();
+    let purge_storage = storage.clone();
+    let purge_passkeys = passkeys.clone();
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            services::users::purge_deleted_accounts(&purge_client, &purge_storage).await;
+            services::proof_of_work::purge_used_challenges(&purge_client).await;
+            purge_passkeys.purge_ceremonies();
+            let mut storage = purge_storage.write().await;
+            storage.purge_attempts();
+            storage.purge_pending_links();
+        }
+    });
+
+    warp::serve(
+        controllers::routes(client, &storage, &passkeys).recover(response::handle_rejection),
+    )
+    .run((host, port))
+    .await;
 }
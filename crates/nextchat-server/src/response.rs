@@ -1,7 +1,7 @@
 //! NextChat Server response module.
 
 use serde::Serialize;
-use warp::{http::StatusCode, reply};
+use warp::{http::StatusCode, reject::Reject, reply, Rejection, Reply};
 
 pub struct Response<T: Serialize> {
     status_code: u16,
@@ -54,3 +54,61 @@ impl Error {
         Response::new(status_code, self)
     }
 }
+
+#[derive(Debug)]
+pub struct Unauthorized {
+    message: String,
+}
+
+impl Unauthorized {
+    /// Create a new unauthorized rejection from a `String`.
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Create a new unauthorized rejection from a `&str`.
+    pub fn from_str(message: &str) -> Self {
+        Self {
+            message: String::from(message),
+        }
+    }
+}
+
+impl Reject for Unauthorized {}
+
+#[derive(Debug)]
+pub struct Forbidden {
+    message: String,
+}
+
+impl Forbidden {
+    /// Create a new forbidden rejection from a `String`.
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Create a new forbidden rejection from a `&str`.
+    pub fn from_str(message: &str) -> Self {
+        Self {
+            message: String::from(message),
+        }
+    }
+}
+
+impl Reject for Forbidden {}
+
+/// Convert the unauthorized rejections to a `401` error response and the forbidden
+/// rejections to a `403` error response, the other rejections are handled by warp.
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    if let Some(unauthorized) = rejection.find::<Unauthorized>() {
+        return Ok(Error::new(unauthorized.message.clone())
+            .to_response(401)
+            .to_reply());
+    } else if let Some(forbidden) = rejection.find::<Forbidden>() {
+        return Ok(Error::new(forbidden.message.clone())
+            .to_response(403)
+            .to_reply());
+    }
+
+    Err(rejection)
+}
@@ -2,7 +2,17 @@
 //!
 //! This module contains all modules of the app.
 
+pub mod admin;
+pub mod audit;
+pub mod authentication;
+pub mod blocks;
 pub mod connection;
+pub mod email;
+pub mod export;
 pub mod friends;
+pub mod invites;
+pub mod passkeys;
+pub mod proof_of_work;
+pub mod two_factor;
 pub mod users;
 pub mod version_checker;
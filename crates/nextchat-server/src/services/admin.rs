@@ -0,0 +1,505 @@
+//! NextChat Server admin service module.
+//!
+//! This module contains the handlers of the admin controller routes:
+//!
+//! `/admin/users`                  -> get_users_handler
+//! `/admin/users/:user_id/role`    -> change_role_handler
+//! `/admin/users/:user_id/ban`     -> ban_handler
+//! `/admin/users/:user_id/ban`     -> unban_handler
+//! `/admin/users/:user_id/logout`  -> logout_handler
+//! `/admin/connections`            -> get_connections_handler
+//! `/admin/announcements`          -> announcement_handler
+//! `/admin/audit-log`              -> get_audit_log_handler
+//!
+//! # Environment variables
+//! - `ADMIN_USERNAMES` _Optional_ - Comma separated usernames granted the `admin` role on startup.
+
+use std::{convert::Infallible, env};
+
+use nextchat_communication::{AnnouncementComposer, StorageType};
+use nextchat_database::{
+    escape_like,
+    models::{admin::*, audit::*, roles::*},
+    Client, Row, Uuid,
+};
+use serde_json::json;
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::{
+        audit::record_event,
+        authentication::{revoke_all_sessions, Authentication},
+    },
+};
+
+/// Grant the `admin` role to the existing users of `ADMIN_USERNAMES`, so the first
+/// admin does not need to be granted in the database.
+pub async fn grant_initial_admins(client: &Client) {
+    let usernames: Vec<String> = env::var("ADMIN_USERNAMES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|username| username.trim().to_lowercase())
+        .filter(|username| !username.is_empty())
+        .collect();
+
+    if usernames.is_empty() {
+        return;
+    }
+
+    match nextchat_database::query(
+        "UPDATE users SET role = $1 WHERE LOWER(username) = ANY($2) AND role <> $1 RETURNING id, username",
+    )
+    .bind(Role::Admin)
+    .bind(&usernames)
+    .fetch_all(client)
+    .await
+    {
+        Err(e) => eprintln!("Cannot grant the admin role: {:?}", e),
+        Ok(users) => {
+            for user in users.iter() {
+                let user_id: Uuid = user.get("id");
+
+                record_event(
+                    client,
+                    AuditEvent::RoleChange,
+                    None,
+                    Some(&user_id),
+                    None,
+                    json!({ "role": Role::Admin, "source": "ADMIN_USERNAMES" }),
+                )
+                .await;
+
+                println!("Admin role granted: {}", user.get::<String, _>("username"));
+            }
+        }
+    }
+}
+
+/// `/admin/users` handler.
+///
+/// Get the users with the fields hidden by the `/users` routes, including the
+/// banned users and the accounts scheduled for deletion.
+///
+/// # Request query
+/// - `?take={number}` _Default_ 10, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+/// - `?search={text}` Only the usernames starting with the text.
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "email": "contact@nextchat.org",
+///         "email_verified_at": null,
+///         "profile_image": "url.png",
+///         "role": "user",
+///         "totp_enabled": false,
+///         "logged": true,
+///         "last_online": "2021-02-02T18:27:08",
+///         "created_at": "2021-02-02T18:27:08",
+///         "invited_by": null,
+///         "banned_at": null,
+///         "ban_reason": null,
+///         "deletion_scheduled_at": null
+///     }
+/// ]
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the search is successful but does not return anything.
+/// - `200` - When the search is successful and returns one or more results.
+///
+/// ## Errors
+/// 1. Cannot get the users.
+pub async fn get_users_handler(
+    _authentication: Authentication,
+    query: AdminUsersQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT id, username, email, email_verified_at, profile_image, role, totp_enabled, logged, last_online, created_at, invited_by, banned_at, ban_reason, deletion_scheduled_at FROM users WHERE ($1::text IS NULL OR username ILIKE $1 || '%' ESCAPE '\\') ORDER BY created_at LIMIT $2 OFFSET $3",
+    )
+    .bind(query.search.as_deref().map(escape_like))
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the users.")
+            .to_response(400)
+            .to_reply()),
+        Ok(users) => {
+            let users: Vec<AdminUserResponse> = users
+                .iter()
+                .map(|user| AdminUserResponse::from_row(user))
+                .collect();
+            Ok(Response::new(if users.is_empty() { 204 } else { 200 }, users).to_reply())
+        }
+    }
+}
+
+/// `/admin/users/:user_id/role` handler.
+///
+/// Grant a role to a user, granting the `user` role revokes the other roles.
+///
+/// # Request body
+/// ```json
+/// {
+///     "role": "moderator"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///     "username": "NextChat",
+///     "role": "moderator"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `404` - When the user does not exist.
+///
+/// ## Errors
+/// 1. You cannot change your own role.
+/// 2. The user #{user_id} does not exist.
+/// 3. Cannot change the role.
+pub async fn change_role_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    body: ChangeRoleBody,
+    ip: String,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    // Avoid leaving the app without admins.
+    if user_id == authentication.user_id {
+        return Ok(Error::from_str("You cannot change your own role.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    match nextchat_database::query(
+        "UPDATE users SET role = $1 WHERE id = $2 RETURNING id, username, role",
+    )
+    .bind(body.role)
+    .bind(&user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Err(nextchat_database::Error::RowNotFound) => {
+            Ok(Error::new(format!("The user #{} does not exist.", user_id))
+                .to_response(404)
+                .to_reply())
+        }
+        Err(_) => Ok(Error::from_str("Cannot change the role.")
+            .to_response(500)
+            .to_reply()),
+        Ok(user) => {
+            record_event(
+                &client,
+                AuditEvent::RoleChange,
+                Some(&authentication.user_id),
+                Some(&user_id),
+                Some(&ip),
+                json!({ "role": body.role }),
+            )
+            .await;
+
+            Ok(Response::new_success(UserRoleResponse {
+                id: user.get("id"),
+                username: user.get("username"),
+                role: user.get("role"),
+            })
+            .to_reply())
+        }
+    }
+}
+
+/// `/admin/users/:user_id/ban` handler.
+///
+/// Ban a user and revoke all its sessions. The banned users cannot sign in
+/// until they are unbanned.
+///
+/// # Request body
+/// ```json
+/// {
+///     "reason": "Spam"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the user has been banned.
+///
+/// ## Errors
+/// 1. You cannot ban yourself.
+/// 2. The user #{user_id} does not exist.
+/// 3. Cannot revoke the sessions.
+pub async fn ban_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    body: BanBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if user_id == authentication.user_id {
+        return Ok(Error::from_str("You cannot ban yourself.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    if nextchat_database::query(
+        "UPDATE users SET banned_at = CURRENT_TIMESTAMP, ban_reason = $1, logged = false WHERE id = $2 RETURNING id",
+    )
+    .bind(&body.reason)
+    .bind(&user_id)
+    .fetch_one(&client)
+    .await
+    .is_err()
+    {
+        return Ok(
+            Error::new(format!("The user #{} does not exist.", user_id))
+                .to_response(404)
+                .to_reply(),
+        );
+    }
+
+    record_event(
+        &client,
+        AuditEvent::Ban,
+        Some(&authentication.user_id),
+        Some(&user_id),
+        Some(&ip),
+        json!({ "reason": body.reason }),
+    )
+    .await;
+
+    match revoke_all_sessions(&client, &storage, &user_id, None).await {
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+        Err(_) => Ok(Error::from_str("Cannot revoke the sessions.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
+
+/// `/admin/users/:user_id/ban` handler.
+///
+/// Unban a user.
+///
+/// ## Status codes
+/// - `204` - When the user has been unbanned.
+///
+/// ## Errors
+/// 1. The user #{user_id} is not banned.
+pub async fn unban_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    ip: String,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "UPDATE users SET banned_at = NULL, ban_reason = NULL WHERE id = $1 AND banned_at IS NOT NULL RETURNING id",
+    )
+    .bind(&user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(_) => {
+            record_event(
+                &client,
+                AuditEvent::Unban,
+                Some(&authentication.user_id),
+                Some(&user_id),
+                Some(&ip),
+                json!({}),
+            )
+            .await;
+
+            Ok(Response::new(204, ()).to_reply())
+        }
+        Err(_) => Ok(
+            Error::new(format!("The user #{} is not banned.", user_id))
+                .to_response(404)
+                .to_reply(),
+        ),
+    }
+}
+
+/// `/admin/users/:user_id/logout` handler.
+///
+/// Revoke all sessions of a user and close its live connections.
+///
+/// ## Status codes
+/// - `204` - When the sessions have been revoked.
+///
+/// ## Errors
+/// 1. Cannot revoke the sessions.
+pub async fn logout_handler(
+    user_id: Uuid,
+    _authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match revoke_all_sessions(&client, &storage, &user_id, None).await {
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+        Err(_) => Ok(Error::from_str("Cannot revoke the sessions.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
+
+/// `/admin/connections` handler.
+///
+/// Get the live websocket connections.
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "user_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "session_id": "5959ad9c-598e-4deb-bcbe-053c1f73b400",
+///         "connected_at": "2021-02-02T18:27:08"
+///     }
+/// ]
+/// ```
+///
+/// ## Status codes
+/// - `204` - When there are not connections.
+/// - `200` - When there are one or more connections.
+pub async fn get_connections_handler(
+    _authentication: Authentication,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    let mut connections: Vec<ConnectionResponse> = storage
+        .read()
+        .await
+        .get_connections()
+        .iter()
+        .map(|connection| ConnectionResponse {
+            user_id: connection.get_user_id(),
+            session_id: connection.get_session_id(),
+            connected_at: connection.get_connected_at(),
+        })
+        .collect();
+    connections.sort_by_key(|connection| connection.connected_at);
+
+    Ok(Response::new(if connections.is_empty() { 204 } else { 200 }, connections).to_reply())
+}
+
+/// `/admin/announcements` handler.
+///
+/// Send a system announcement to all live connections.
+///
+/// # Request body
+/// ```json
+/// {
+///     "message": "The server will restart in 5 minutes."
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "recipients": 12
+/// }
+/// ```
+///
+/// ## Errors
+/// 1. You must enter the message.
+pub async fn announcement_handler(
+    _authentication: Authentication,
+    body: AnnouncementBody,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    let message: String = String::from(body.message.trim());
+    if message.is_empty() {
+        return Ok(Error::from_str("You must enter the message.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    let recipients: usize = storage
+        .read()
+        .await
+        .get_connections()
+        .iter()
+        .filter(|connection| {
+            connection
+                .send_packet(Box::new(AnnouncementComposer::new(message.clone())))
+                .is_ok()
+        })
+        .count();
+
+    Ok(Response::new_success(AnnouncementResponse { recipients }).to_reply())
+}
+
+/// `/admin/audit-log` handler.
+///
+/// Get the events of the audit log, from the newest to the oldest.
+///
+/// # Request query
+/// - `?user_id={user_id}` Only the events where the user is the actor or the target.
+/// - `?event={event}` Only the events of a type, e.g. `signin_failed`.
+/// - `?from={timestamp}` Only the events since the timestamp, e.g. `2021-02-02T00:00:00`.
+/// - `?to={timestamp}` Only the events before the timestamp.
+/// - `?take={number}` _Default_ 50, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "id": 1,
+///         "event": "signin",
+///         "actor_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "target_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "ip": "127.0.0.1",
+///         "metadata": {
+///             "method": "password",
+///             "device_name": "NextChat Desktop",
+///             "user_agent": "Mozilla/5.0"
+///         },
+///         "created_at": "2021-02-02T18:27:08"
+///     }
+/// ]
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the search is successful but does not return anything.
+/// - `200` - When the search is successful and returns one or more results.
+///
+/// ## Errors
+/// 1. Cannot get the audit log.
+pub async fn get_audit_log_handler(
+    _authentication: Authentication,
+    query: AuditLogQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT id, event, actor_id, target_id, ip, metadata, created_at FROM audit_log WHERE ($1::uuid IS NULL OR actor_id = $1 OR target_id = $1) AND ($2::audit_event IS NULL OR event = $2) AND ($3::timestamp IS NULL OR created_at >= $3) AND ($4::timestamp IS NULL OR created_at < $4) ORDER BY id DESC LIMIT $5 OFFSET $6",
+    )
+    .bind(query.user_id)
+    .bind(query.event)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.take.unwrap_or(50).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the audit log.")
+            .to_response(400)
+            .to_reply()),
+        Ok(events) => {
+            let events: Vec<AuditLogResponse> = events
+                .iter()
+                .map(|event| AuditLogResponse::from_row(event))
+                .collect();
+            Ok(Response::new(if events.is_empty() { 204 } else { 200 }, events).to_reply())
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! NextChat Server audit service module.
+//!
+//! This module contains the function to write the security events to the
+//! append-only `audit_log` table. See `/admin/audit-log` to query them.
+
+use nextchat_database::{models::audit::AuditEvent, Client, Uuid};
+use serde_json::Value;
+
+/// Write an event to the audit log.
+///
+/// The `actor_id` is the user that made the request, `None` for anonymous
+/// requests and the system tasks, and the `target_id` is the affected user.
+///
+/// A failure is only logged, so the audit log never breaks the requests.
+pub async fn record_event(
+    client: &Client,
+    event: AuditEvent,
+    actor_id: Option<&Uuid>,
+    target_id: Option<&Uuid>,
+    ip: Option<&str>,
+    metadata: Value,
+) {
+    if let Err(e) = nextchat_database::query(
+        "INSERT INTO audit_log(event, actor_id, target_id, ip, metadata) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(event)
+    .bind(actor_id)
+    .bind(target_id)
+    .bind(ip)
+    .bind(&metadata)
+    .execute(client)
+    .await
+    {
+        eprintln!("Cannot write the {:?} event to the audit log: {:?}", event, e);
+    }
+}
@@ -0,0 +1,424 @@
+//! NextChat Server authentication service module.
+//!
+//! This module contains the functions to create, validate and revoke the
+//! sessions of the users, and the handlers of the authentication controller routes:
+//!
+//! `/auth/refresh`                 -> refresh_handler
+//! `/auth/logout`                  -> logout_handler
+//! `/auth/logout-all`              -> logout_all_handler
+//! `/auth/sessions`                -> get_sessions_handler
+//! `/auth/sessions/:session_id`    -> delete_session_handler
+
+use std::convert::Infallible;
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{
+        roles::{Permission, Role},
+        sessions::{RefreshBody, SessionModel, SessionResponse, SessionTokensResponse},
+    },
+    Client, Row, Uuid,
+};
+use nextchat_security::{
+    decode_token, encode_token, generate_random_token, get_timestamp, hash_token,
+};
+use serde::{Deserialize, Serialize};
+use warp::{Rejection, Reply};
+
+use crate::response::{Error, Forbidden, Response, Unauthorized};
+
+/// Lifetime of the access tokens in seconds (15 minutes).
+const ACCESS_TOKEN_LIFETIME: u64 = 60 * 15;
+
+/// Days without use before a refresh token expires.
+const REFRESH_TOKEN_LIFETIME_DAYS: i32 = 30;
+
+/// Max length of the device name.
+const DEVICE_NAME_MAX_LENGTH: usize = 50;
+
+#[derive(Serialize, Deserialize)]
+struct AccessTokenClaims {
+    sub: Uuid,
+    sid: Uuid,
+    iat: u64,
+    exp: u64,
+}
+
+/// The user and the session of an authenticated request.
+#[derive(Clone, Copy)]
+pub struct Authentication {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+}
+
+/// Generate a signed access token for a session.
+fn generate_access_token(user_id: &Uuid, session_id: &Uuid) -> Result<String, String> {
+    let now = get_timestamp();
+
+    encode_token(&AccessTokenClaims {
+        sub: *user_id,
+        sid: *session_id,
+        iat: now,
+        exp: now + ACCESS_TOKEN_LIFETIME,
+    })
+}
+
+/// Create a new session for the user and get its access and refresh tokens.
+pub async fn create_session(
+    client: &Client,
+    user_id: &Uuid,
+    device_name: Option<String>,
+    user_agent: &str,
+    ip: &str,
+) -> Result<SessionTokensResponse, String> {
+    let session_id: Uuid = Uuid::new_v4();
+    let refresh_token: String = generate_random_token();
+
+    let device_name: String = match device_name {
+        Some(name) if !name.trim().is_empty() => {
+            name.trim().chars().take(DEVICE_NAME_MAX_LENGTH).collect()
+        }
+        _ => String::from("Unknown device"),
+    };
+
+    match nextchat_database::query(
+        "INSERT INTO sessions(id, user_id, refresh_token, device_name, user_agent, ip) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&session_id)
+    .bind(user_id)
+    .bind(hash_token(&refresh_token))
+    .bind(&device_name)
+    .bind(user_agent)
+    .bind(ip)
+    .execute(client)
+    .await
+    {
+        Err(_) => Err(String::from("Cannot create the session.")),
+        Ok(_) => Ok(SessionTokensResponse {
+            access_token: generate_access_token(user_id, &session_id)?,
+            refresh_token,
+        }),
+    }
+}
+
+/// Validate an access token and check that its session was not revoked.
+pub async fn authenticate(client: &Client, token: &str) -> Result<Authentication, Rejection> {
+    let claims = match decode_token::<AccessTokenClaims>(token) {
+        Ok(claims) => claims,
+        Err(e) => {
+            return Err(warp::reject::custom(Unauthorized::new(e)));
+        }
+    };
+
+    let is_outdated: bool = match nextchat_database::query(
+        "SELECT last_used < CURRENT_TIMESTAMP - INTERVAL '5 minutes' AS outdated FROM sessions WHERE id = $1 AND user_id = $2",
+    )
+    .bind(&claims.sid)
+    .bind(&claims.sub)
+    .fetch_one(client)
+    .await
+    {
+        Ok(row) => row.get("outdated"),
+        Err(_) => {
+            return Err(warp::reject::custom(Unauthorized::from_str(
+                "The session has been revoked.",
+            )));
+        }
+    };
+
+    // Only refresh the last use every 5 minutes to avoid a write on each request.
+    if is_outdated {
+        nextchat_database::query("UPDATE sessions SET last_used = CURRENT_TIMESTAMP WHERE id = $1")
+            .bind(&claims.sid)
+            .execute(client)
+            .await
+            .ok();
+    }
+
+    Ok(Authentication {
+        user_id: claims.sub,
+        session_id: claims.sid,
+    })
+}
+
+/// Validate the `Authorization: Bearer {token}` header.
+pub async fn authenticate_header(
+    header: Option<String>,
+    client: Client,
+) -> Result<Authentication, Rejection> {
+    match header.as_deref().and_then(|h| h.strip_prefix("Bearer ")) {
+        Some(token) => authenticate(&client, token).await,
+        None => Err(warp::reject::custom(Unauthorized::from_str(
+            "You must be authenticated.",
+        ))),
+    }
+}
+
+/// Check that the role of the authenticated user has a permission.
+///
+/// The role is read from the database, so the revoked roles are applied immediately.
+pub async fn authorize(
+    authentication: Authentication,
+    permission: Permission,
+    client: Client,
+) -> Result<Authentication, Rejection> {
+    let role: Option<Role> = nextchat_database::query("SELECT role FROM users WHERE id = $1")
+        .bind(&authentication.user_id)
+        .fetch_one(&client)
+        .await
+        .ok()
+        .map(|user| user.get("role"));
+
+    match role {
+        Some(role) if role.has_permission(permission) => Ok(authentication),
+        _ => Err(warp::reject::custom(Forbidden::from_str(
+            "You do not have permission to do this.",
+        ))),
+    }
+}
+
+/// Send the session closed packet to the live connections of the sessions and close them.
+async fn close_sessions(storage: &StorageType, session_ids: &[Uuid]) {
+    let mut storage = storage.write().await;
+
+    for session_id in session_ids.iter() {
+        storage.close_connection(session_id);
+    }
+}
+
+/// Revoke a session of the user and close its live connection.
+pub async fn revoke_session(
+    client: &Client,
+    storage: &StorageType,
+    user_id: &Uuid,
+    session_id: &Uuid,
+) -> Result<bool, nextchat_database::Error> {
+    let result = nextchat_database::query("DELETE FROM sessions WHERE id = $1 AND user_id = $2")
+        .bind(session_id)
+        .bind(user_id)
+        .execute(client)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    close_sessions(storage, &[*session_id]).await;
+
+    Ok(true)
+}
+
+/// Revoke all sessions of the user, except the `keep` session, and close their
+/// live connections.
+pub async fn revoke_all_sessions(
+    client: &Client,
+    storage: &StorageType,
+    user_id: &Uuid,
+    keep: Option<&Uuid>,
+) -> Result<(), nextchat_database::Error> {
+    let sessions = nextchat_database::query(
+        "DELETE FROM sessions WHERE user_id = $1 AND ($2::uuid IS NULL OR id <> $2) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(keep.cloned())
+    .fetch_all(client)
+    .await?;
+
+    let session_ids: Vec<Uuid> = sessions.iter().map(|row| row.get("id")).collect();
+    close_sessions(storage, &session_ids).await;
+
+    Ok(())
+}
+
+/// `/auth/refresh` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "refresh_token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "access_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "refresh_token": "Xk3bO0e7Lw2a..."
+/// }
+/// ```
+///
+/// ## Errors
+/// 1. The refresh token is invalid or has expired.
+/// 2. Cannot sign the token.
+pub async fn refresh_handler(body: RefreshBody, client: Client) -> Result<impl Reply, Infallible> {
+    let refresh_token: String = generate_random_token();
+
+    // Rotate the refresh token of the session.
+    let sql = format!("UPDATE sessions SET refresh_token = $1, last_used = CURRENT_TIMESTAMP WHERE refresh_token = $2 AND last_used > CURRENT_TIMESTAMP - INTERVAL '{} days' RETURNING id, user_id", REFRESH_TOKEN_LIFETIME_DAYS);
+    match nextchat_database::query(&sql)
+        .bind(hash_token(&refresh_token))
+        .bind(hash_token(&body.refresh_token))
+        .fetch_one(&client)
+        .await
+    {
+        Err(_) => Ok(
+            Error::from_str("The refresh token is invalid or has expired.")
+                .to_response(401)
+                .to_reply(),
+        ),
+        Ok(session) => {
+            let user_id: Uuid = session.get("user_id");
+            let session_id: Uuid = session.get("id");
+
+            match generate_access_token(&user_id, &session_id) {
+                Ok(access_token) => Ok(Response::new_success(SessionTokensResponse {
+                    access_token,
+                    refresh_token,
+                })
+                .to_reply()),
+                Err(e) => Ok(Error::new(e).to_response(400).to_reply()),
+            }
+        }
+    }
+}
+
+/// `/auth/logout` handler.
+///
+/// Revoke the current session.
+///
+/// ## Status codes
+/// - `204` - When the session was revoked.
+///
+/// ## Errors
+/// 1. Cannot revoke the session.
+pub async fn logout_handler(
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match revoke_session(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &authentication.session_id,
+    )
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot revoke the session.")
+            .to_response(400)
+            .to_reply()),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/auth/logout-all` handler.
+///
+/// Revoke all sessions of the user, including the current session.
+///
+/// ## Status codes
+/// - `204` - When the sessions were revoked.
+///
+/// ## Errors
+/// 1. Cannot revoke the sessions.
+pub async fn logout_all_handler(
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match revoke_all_sessions(&client, &storage, &authentication.user_id, None).await {
+        Err(_) => Ok(Error::from_str("Cannot revoke the sessions.")
+            .to_response(400)
+            .to_reply()),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/auth/sessions` handler.
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "id": "3f0c1a8e-6d4b-4f7a-9a0e-2b7c9d1e5f60",
+///         "device_name": "NextChat Desktop",
+///         "user_agent": "NextChat/0.1.0-alpha1 (Windows)",
+///         "ip": "127.0.0.1",
+///         "created_at": "2021-02-02T18:27:08",
+///         "last_used": "2021-02-02T18:27:08",
+///         "connected": true,
+///         "current": true
+///     }
+/// ]
+/// ```
+///
+/// ## Errors
+/// 1. Cannot get the sessions.
+pub async fn get_sessions_handler(
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT id, device_name, user_agent, ip, created_at, last_used FROM sessions WHERE user_id = $1 ORDER BY last_used DESC",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the sessions.")
+            .to_response(400)
+            .to_reply()),
+        Ok(sessions) => {
+            let storage = storage.read().await;
+
+            let sessions: Vec<SessionResponse> = sessions
+                .iter()
+                .map(|row| {
+                    let session = SessionModel::from_row(row);
+
+                    SessionResponse {
+                        id: session.get_id(),
+                        device_name: session.get_device_name(),
+                        user_agent: session.get_user_agent(),
+                        ip: session.get_ip(),
+                        created_at: session.get_created_at(),
+                        last_used: session.get_last_used(),
+                        connected: storage.is_connected(&session.get_id()),
+                        current: session.get_id() == authentication.session_id,
+                    }
+                })
+                .collect();
+
+            Ok(Response::new_success(sessions).to_reply())
+        }
+    }
+}
+
+/// `/auth/sessions/:session_id` handler.
+///
+/// Revoke a session of the user and close its live connection.
+///
+/// ## Status codes
+/// - `204` - When the session was revoked.
+///
+/// ## Errors
+/// 1. Cannot revoke the session.
+/// 2. The session #{session_id} does not exist.
+pub async fn delete_session_handler(
+    session_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match revoke_session(&client, &storage, &authentication.user_id, &session_id).await {
+        Err(_) => Ok(Error::from_str("Cannot revoke the session.")
+            .to_response(400)
+            .to_reply()),
+        Ok(false) => Ok(
+            Error::new(format!("The session #{} does not exist.", session_id))
+                .to_response(404)
+                .to_reply(),
+        ),
+        Ok(true) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
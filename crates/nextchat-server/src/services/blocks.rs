@@ -0,0 +1,217 @@
+//! NextChat Server blocks service module.
+//!
+//! This module contains the handlers of the blocks controller routes:
+//!
+//! `/users/blocks`             -> get_blocks_handler
+//! `/users/blocks/:user_id`    -> block_handler
+//! `/users/blocks/:user_id`    -> unblock_handler
+//!
+//! The blocked users cannot send friend requests to the blocker or find the
+//! blocker in `/users/all`, `/users/search`, `/users/find`,
+//! `/friends/get-of/:user_id` and `/friends/:user_one/:user_two`.
+
+use std::convert::Infallible;
+
+use nextchat_database::{
+    models::{blocks::*, users::UserDataResponse},
+    Client, NaiveDateTime, Row, Uuid,
+};
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::authentication::Authentication,
+};
+
+/// `/users/blocks` handler.
+///
+/// # Request query
+/// - `?take={number}` _Default_ 10, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "user": {
+///             "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "username": "NextChat",
+///             "profile_image": "url.png"
+///         },
+///         "blocked_at": "2021-02-02T18:27:08"
+///     }
+/// ]
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the user has not blocked anyone.
+/// - `200` - When the user has blocked one or more users.
+///
+/// ## Errors
+/// 1. Cannot get the blocked users.
+pub async fn get_blocks_handler(
+    authentication: Authentication,
+    query: BlocksQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT users.id, users.username, users.profile_image, blocks.created_at AS blocked_at FROM blocks INNER JOIN users ON users.id = blocks.blocked WHERE blocks.blocker = $1 ORDER BY blocks.created_at DESC LIMIT $2 OFFSET $3",
+    )
+    .bind(&authentication.user_id)
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the blocked users.")
+            .to_response(400)
+            .to_reply()),
+        Ok(blocks) => {
+            let blocks: Vec<BlockResponse> = blocks.iter().map(BlockResponse::from_row).collect();
+            Ok(Response::new(if blocks.is_empty() { 204 } else { 200 }, blocks).to_reply())
+        }
+    }
+}
+
+/// `/users/blocks/:user_id` handler.
+///
+/// Block the user and remove the friendship or the friend requests with it.
+///
+/// # Response
+/// ```json
+/// {
+///     "user": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "profile_image": "url.png"
+///     },
+///     "blocked_at": "2021-02-02T18:27:08"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `201` - When the user was blocked.
+///
+/// ## Errors
+/// 1. You cannot block yourself.
+/// 2. The user does not exist.
+/// 3. The user is already blocked.
+/// 4. Cannot block the user.
+pub async fn block_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    if user_id == authentication.user_id {
+        return Ok(Error::from_str("You cannot block yourself.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    let user = match nextchat_database::query(
+        "SELECT id, username, profile_image FROM users WHERE id = $1",
+    )
+    .bind(&user_id)
+    .fetch_optional(&client)
+    .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Ok(Error::from_str("The user does not exist.")
+                .to_response(404)
+                .to_reply());
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot block the user.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    let mut transaction = match client.begin().await {
+        Ok(transaction) => transaction,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot block the user.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    let blocked_at: NaiveDateTime = match nextchat_database::query(
+        "INSERT INTO blocks(blocker, blocked) VALUES ($1, $2) ON CONFLICT DO NOTHING RETURNING created_at",
+    )
+    .bind(&authentication.user_id)
+    .bind(&user_id)
+    .fetch_optional(&mut transaction)
+    .await
+    {
+        Ok(Some(block)) => block.get("created_at"),
+        Ok(None) => {
+            return Ok(Error::from_str("The user is already blocked.")
+                .to_response(400)
+                .to_reply());
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot block the user.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    let removed = nextchat_database::query(
+        "DELETE FROM friends WHERE (transmitter = $1 AND receiver = $2) OR (transmitter = $2 AND receiver = $1)",
+    )
+    .bind(&authentication.user_id)
+    .bind(&user_id)
+    .execute(&mut transaction)
+    .await
+    .is_ok();
+
+    if !removed || transaction.commit().await.is_err() {
+        return Ok(Error::from_str("Cannot block the user.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    Ok(Response::new(
+        201,
+        BlockResponse {
+            user: UserDataResponse::from_row(&user),
+            blocked_at,
+        },
+    )
+    .to_reply())
+}
+
+/// `/users/blocks/:user_id` handler.
+///
+/// Unblock the user, the removed friendship is not restored.
+///
+/// ## Status codes
+/// - `204` - When the user was unblocked.
+///
+/// ## Errors
+/// 1. Cannot unblock the user.
+/// 2. The user is not blocked.
+pub async fn unblock_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query("DELETE FROM blocks WHERE blocker = $1 AND blocked = $2")
+        .bind(&authentication.user_id)
+        .bind(&user_id)
+        .execute(&client)
+        .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot unblock the user.")
+            .to_response(400)
+            .to_reply()),
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(Error::from_str("The user is not blocked.")
+                .to_response(404)
+                .to_reply())
+        }
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
@@ -1,15 +1,38 @@
 //! NextChat Server connection service module.
 //!
 //! This module contains the `on_message`, `on_close_connection` and `on_new_connection` events
-//! of a WebSocket connection.
-
-use futures::{FutureExt, StreamExt};
-use nextchat_communication::{CommunicationMessage, Connection, StorageType};
-use nextchat_database::{Client, Uuid};
-use tokio::sync::mpsc;
+//! of a WebSocket connection, and the `on_new_link_connection` event of a device waiting
+//! to be linked.
+//!
+//! # Device linking
+//! 1. The new device opens `/connection/link` and receives `/link_token {pairing_token} {expires_in}`.
+//! 2. The device shows the pairing token as a QR code.
+//! 3. An authenticated device scans it and sends `/link_confirm {pairing_token}`.
+//! 4. The new device receives `/link_session {user_id} {access_token} {refresh_token}` and
+//!    the link connection is closed.
+
+use std::time::Duration;
+
+use futures::{future, FutureExt, StreamExt};
+use nextchat_communication::{
+    CommunicationMessage, Connection, LinkSessionComposer, LinkTokenComposer, PacketComposer,
+    StorageType,
+};
+use nextchat_database::{models::audit::AuditEvent, Client, Uuid};
+use nextchat_security::generate_random_token;
+use serde_json::json;
+use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use warp::ws::{Message, WebSocket};
 
+use super::{
+    audit::record_event,
+    authentication::{create_session, Authentication},
+};
+
+/// Lifetime of the pairing tokens in seconds (2 minutes).
+const PAIRING_TOKEN_LIFETIME: u64 = 60 * 2;
+
 /// This functions handles all received messages and run the correspondent event.
 async fn on_new_message(
     message: Message,
@@ -24,33 +47,35 @@ async fn on_new_message(
         }
     };
 
-    // Get the communication message.
-    let cmessage = CommunicationMessage::from_string(String::from(message))
-        .expect("Cannot parse the message string to a communication message.");
+    // Get the communication message, the malformed messages are ignored.
+    let cmessage = match CommunicationMessage::from_string(String::from(message)) {
+        Ok(cmessage) => cmessage,
+        Err(_) => {
+            return;
+        }
+    };
 
     // Run a event.
     nextchat_communication::run_event(connection, &cmessage, client, storage).await;
 }
 
 /// Remove a connection from the storage.
-async fn on_close_connection(user_id: &Uuid, _client: &Client, storage: &StorageType) {
+async fn on_close_connection(session_id: &Uuid, _client: &Client, storage: &StorageType) {
     let mut storage = storage.write().await;
 
-    storage.remove_connection(user_id);
+    storage.remove_connection(session_id);
 
-    println!("User disconnected: {}", user_id);
+    println!("Session disconnected: {}", session_id);
 }
 
 /// This functions handle the socket messages and add the new connection
 /// to the storage.
 pub async fn on_new_connection(
     socket: WebSocket,
-    user_id: Uuid,
+    authentication: Authentication,
     client: Client,
     storage: StorageType,
 ) {
-    let mut storage_mut = storage.write().await;
-
     // Split the socket into a sender and receive of messages.
     let (user_socket_tx, mut user_socket_rx) = socket.split();
     let (tx, rx) = mpsc::unbounded_channel();
@@ -62,11 +87,14 @@ pub async fn on_new_connection(
         }
     }));
 
-    let connection = Connection::new(&user_id, &tx);
+    let connection = Connection::new(&authentication.user_id, &authentication.session_id, &tx);
 
-    storage_mut.add_connection(&user_id, &connection);
+    storage.write().await.add_connection(&connection);
 
-    println!("User connected: {}", user_id);
+    println!(
+        "User connected: {} (Session: {})",
+        authentication.user_id, authentication.session_id
+    );
 
     // Handle the socket messages.
     while let Some(result) = user_socket_rx.next().await {
@@ -74,12 +102,98 @@ pub async fn on_new_connection(
         let message = match result {
             Ok(message) => message,
             Err(_) => {
-                return;
+                break;
             }
         };
 
         on_new_message(message, &connection, &client, &storage).await;
     }
 
-    on_close_connection(&user_id, &client, &storage).await;
+    on_close_connection(&authentication.session_id, &client, &storage).await;
+}
+
+/// This function handles the connection of a device waiting to be linked and sends
+/// it a new session when the pairing token is confirmed by an authenticated connection.
+pub async fn on_new_link_connection(
+    socket: WebSocket,
+    device_name: Option<String>,
+    ip: String,
+    user_agent: String,
+    client: Client,
+    storage: StorageType,
+) {
+    // Split the socket into a sender and receive of messages.
+    let (user_socket_tx, mut user_socket_rx) = socket.split();
+    let (tx, rx) = mpsc::unbounded_channel();
+    let rx = UnboundedReceiverStream::new(rx);
+
+    tokio::task::spawn(rx.forward(user_socket_tx).map(|result| {
+        if let Err(e) = result {
+            eprintln!("WebSocket send error: {:?}", e);
+        }
+    }));
+
+    let send_packet = |packet: &dyn PacketComposer| {
+        tx.send(Ok(Message::text(packet.to_message().to_string())))
+            .ok();
+    };
+
+    let pairing_token: String = generate_random_token();
+    let (sender, receiver) = oneshot::channel::<Uuid>();
+
+    let lifetime = Duration::from_secs(PAIRING_TOKEN_LIFETIME);
+    if !storage
+        .write()
+        .await
+        .add_pending_link(&pairing_token, &ip, lifetime, sender)
+    {
+        tx.send(Ok(Message::close())).ok();
+        return;
+    }
+
+    send_packet(&LinkTokenComposer::new(
+        pairing_token.clone(),
+        PAIRING_TOKEN_LIFETIME,
+    ));
+
+    // Wait for the confirmation until the pairing token expires or the device disconnects.
+    let closed = Box::pin(async move { while let Some(Ok(_)) = user_socket_rx.next().await {} });
+    let confirmation = tokio::time::timeout(lifetime, future::select(receiver, closed)).await;
+
+    storage.write().await.take_pending_link(&pairing_token);
+
+    if let Ok(future::Either::Left((Ok(user_id), _))) = confirmation {
+        let metadata = json!({
+            "method": "link",
+            "device_name": device_name,
+            "user_agent": user_agent,
+        });
+
+        match create_session(&client, &user_id, device_name, &user_agent, &ip).await {
+            Ok(tokens) => {
+                record_event(
+                    &client,
+                    AuditEvent::Signin,
+                    Some(&user_id),
+                    Some(&user_id),
+                    Some(&ip),
+                    metadata,
+                )
+                .await;
+
+                send_packet(&LinkSessionComposer::new(
+                    user_id.to_string(),
+                    tokens.access_token,
+                    tokens.refresh_token,
+                ));
+
+                println!("Device linked: {}", user_id);
+            }
+            Err(e) => {
+                eprintln!("Cannot link the device of the user #{}: {}", user_id, e);
+            }
+        }
+    }
+
+    tx.send(Ok(Message::close())).ok();
 }
@@ -0,0 +1,438 @@
+//! NextChat Server email service module.
+//!
+//! This module contains the handlers of the email controller routes:
+//!
+//! `/users/email`          -> get_email_handler
+//! `/users/email`          -> change_email_handler
+//! `/users/email/resend`   -> resend_handler
+//! `/users/email/verify`   -> verify_handler
+//!
+//! # Environment variables
+//! - `EMAIL_VERIFICATION_URL` _Default_ `http://localhost:3000/verify-email` - The page
+//!   of the client that sends the token of the link to `/users/email/verify`.
+//! - `REQUIRE_VERIFIED_EMAIL` _Default_ `false` - Require a verified email to send
+//!   friend requests.
+
+use std::{convert::Infallible, env};
+
+use nextchat_communication::StorageType;
+use nextchat_database::{models::email::*, Client, Row, Uuid};
+use nextchat_mailer::{validate_email, Email, MailerType};
+use nextchat_security::{generate_random_token, hash_token};
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::{authentication::Authentication, users::confirm_password},
+};
+
+/// Lifetime of the verification tokens in hours.
+const VERIFICATION_LIFETIME_HOURS: i32 = 24;
+
+/// Min time between two verification emails in seconds.
+const RESEND_INTERVAL_SECONDS: i32 = 60;
+
+/// Check if the `REQUIRE_VERIFIED_EMAIL` environment variable requires a verified
+/// email to send friend requests.
+fn requires_verified_email() -> bool {
+    env::var("REQUIRE_VERIFIED_EMAIL")
+        .map(|value| value.trim().eq_ignore_ascii_case("true") || value.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// Check if the user can send friend requests, the user must have a verified
+/// email when `REQUIRE_VERIFIED_EMAIL` is enabled.
+pub async fn can_send_friend_requests(client: &Client, user_id: &Uuid) -> bool {
+    if !requires_verified_email() {
+        return true;
+    }
+
+    nextchat_database::query(
+        "SELECT id FROM users WHERE id = $1 AND email IS NOT NULL AND email_verified_at IS NOT NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(client)
+    .await
+    .map(|user| user.is_some())
+    .unwrap_or(false)
+}
+
+/// Reserve the sending of a verification email for the user, so each user can only
+/// send one every `RESEND_INTERVAL_SECONDS` to any address.
+///
+/// Returns `false` when the last verification email was sent too recently.
+async fn reserve_verification_email(
+    client: &Client,
+    user_id: &Uuid,
+) -> Result<bool, nextchat_database::Error> {
+    nextchat_database::query(
+        "UPDATE users SET last_verification_sent_at = CURRENT_TIMESTAMP WHERE id = $1 AND (last_verification_sent_at IS NULL OR last_verification_sent_at <= CURRENT_TIMESTAMP - make_interval(secs => $2)) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(RESEND_INTERVAL_SECONDS)
+    .fetch_optional(client)
+    .await
+    .map(|user| user.is_some())
+}
+
+/// Create a new verification token for the email of the user, replacing the
+/// previous tokens, and send it to the email.
+async fn send_verification(
+    client: &Client,
+    mailer: &MailerType,
+    user_id: &Uuid,
+    email: &str,
+) -> Result<(), &'static str> {
+    let token: String = generate_random_token();
+
+    let mut transaction = match client.begin().await {
+        Ok(transaction) => transaction,
+        Err(_) => return Err("Cannot create the verification token."),
+    };
+
+    let created = nextchat_database::query("DELETE FROM email_verifications WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut transaction)
+        .await
+        .is_ok()
+        && nextchat_database::query(
+            "INSERT INTO email_verifications(token, user_id, email, expires_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(hours => $4))",
+        )
+        .bind(hash_token(&token))
+        .bind(user_id)
+        .bind(email)
+        .bind(VERIFICATION_LIFETIME_HOURS)
+        .execute(&mut transaction)
+        .await
+        .is_ok();
+
+    if !created || transaction.commit().await.is_err() {
+        return Err("Cannot create the verification token.");
+    }
+
+    let url: String = env::var("EMAIL_VERIFICATION_URL")
+        .unwrap_or_else(|_| String::from("http://localhost:3000/verify-email"));
+
+    let body: String = format!(
+        "Open the following link to verify your NextChat email:\n\n{}?token={}\n\nThe link expires in {} hours. If you did not add this email to a NextChat account, ignore this message.",
+        url, token, VERIFICATION_LIFETIME_HOURS
+    );
+
+    match mailer
+        .send(&Email::new(email, "Verify your NextChat email", &body))
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            eprintln!(
+                "Cannot send the verification email of the user #{}: {}",
+                user_id, e
+            );
+            Err("Cannot send the verification email.")
+        }
+    }
+}
+
+/// `/users/email` handler.
+///
+/// # Response
+/// ```json
+/// {
+///     "email": "contact@nextchat.org",
+///     "email_verified": false
+/// }
+/// ```
+///
+/// ## Errors
+/// 1. Cannot get the email.
+pub async fn get_email_handler(
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query("SELECT email, email_verified_at FROM users WHERE id = $1")
+        .bind(&authentication.user_id)
+        .fetch_one(&client)
+        .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the email.")
+            .to_response(400)
+            .to_reply()),
+        Ok(user) => Ok(Response::new_success(EmailResponse {
+            email: user.get("email"),
+            email_verified: user
+                .get::<Option<nextchat_database::NaiveDateTime>, _>("email_verified_at")
+                .is_some(),
+        })
+        .to_reply()),
+    }
+}
+
+/// `/users/email` handler.
+///
+/// Add or change the email of the user and send a verification link to it. The
+/// email is not verified until the link is opened.
+///
+/// # Request body
+/// ```json
+/// {
+///     "email": "contact@nextchat.org",
+///     "password": "12345678"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "email": "contact@nextchat.org",
+///     "email_verified": false
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `202` - When the email was changed and the verification email was sent.
+/// - `429` - When the last verification email was sent less than a minute ago.
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. You must enter the email.
+/// 3. The email is too long.
+/// 4. The email is invalid.
+/// 5. The email is already in use.
+/// 6. Wait a minute before sending another verification email.
+/// 7. Cannot change the email.
+/// 8. Cannot create the verification token.
+/// 9. Cannot send the verification email.
+/// 10. Too many failed attempts, try again in {seconds} seconds.
+pub async fn change_email_handler(
+    authentication: Authentication,
+    body: ChangeEmailBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+    mailer: MailerType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    let email: String = match validate_email(&body.email) {
+        Ok(email) => email,
+        Err(e) => return Ok(Error::from_str(e).to_response(400).to_reply()),
+    };
+
+    match nextchat_database::query(
+        "SELECT id FROM users WHERE LOWER(email) = LOWER($1) AND email_verified_at IS NOT NULL",
+    )
+    .bind(&email)
+    .fetch_optional(&client)
+    .await
+    {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Ok(Error::from_str("The email is already in use.")
+                .to_response(400)
+                .to_reply());
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot change the email.")
+                .to_response(400)
+                .to_reply());
+        }
+    }
+
+    match reserve_verification_email(&client, &authentication.user_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(
+                Error::from_str("Wait a minute before sending another verification email.")
+                    .to_response(429)
+                    .to_reply(),
+            );
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot change the email.")
+                .to_response(400)
+                .to_reply());
+        }
+    }
+
+    if nextchat_database::query(
+        "UPDATE users SET email = $1, email_verified_at = NULL WHERE id = $2",
+    )
+    .bind(&email)
+    .bind(&authentication.user_id)
+    .execute(&client)
+    .await
+    .is_err()
+    {
+        return Ok(Error::from_str("Cannot change the email.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    match send_verification(&client, &mailer, &authentication.user_id, &email).await {
+        Err(e) => Ok(Error::from_str(e).to_response(400).to_reply()),
+        Ok(_) => Ok(Response::new(
+            202,
+            EmailResponse {
+                email: Some(email),
+                email_verified: false,
+            },
+        )
+        .to_reply()),
+    }
+}
+
+/// `/users/email/resend` handler.
+///
+/// Send a new verification link to the email of the user, the previous links
+/// stop working.
+///
+/// ## Status codes
+/// - `204` - When the verification email was sent.
+/// - `429` - When the last verification email was sent less than a minute ago.
+///
+/// ## Errors
+/// 1. You must add an email.
+/// 2. The email is already verified.
+/// 3. Wait a minute before sending another verification email.
+/// 4. Cannot create the verification token.
+/// 5. Cannot send the verification email.
+pub async fn resend_handler(
+    authentication: Authentication,
+    client: Client,
+    mailer: MailerType,
+) -> Result<impl Reply, Infallible> {
+    let user =
+        match nextchat_database::query("SELECT email, email_verified_at FROM users WHERE id = $1")
+            .bind(&authentication.user_id)
+            .fetch_one(&client)
+            .await
+        {
+            Ok(user) => user,
+            Err(_) => {
+                return Ok(Error::from_str("You must add an email.")
+                    .to_response(400)
+                    .to_reply());
+            }
+        };
+
+    let email: String = match user.get::<Option<String>, _>("email") {
+        Some(email) => email,
+        None => {
+            return Ok(Error::from_str("You must add an email.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    if user
+        .get::<Option<nextchat_database::NaiveDateTime>, _>("email_verified_at")
+        .is_some()
+    {
+        return Ok(Error::from_str("The email is already verified.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    match reserve_verification_email(&client, &authentication.user_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(
+                Error::from_str("Wait a minute before sending another verification email.")
+                    .to_response(429)
+                    .to_reply(),
+            );
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot create the verification token.")
+                .to_response(400)
+                .to_reply());
+        }
+    }
+
+    match send_verification(&client, &mailer, &authentication.user_id, &email).await {
+        Err(e) => Ok(Error::from_str(e).to_response(400).to_reply()),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/users/email/verify` handler.
+///
+/// Verify the email with the token of the verification link. The token can only
+/// be used once and expires after 24 hours.
+///
+/// # Request body
+/// ```json
+/// {
+///     "token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "email": "contact@nextchat.org",
+///     "email_verified": true
+/// }
+/// ```
+///
+/// ## Errors
+/// 1. The verification link is invalid or has expired.
+/// 2. The email is already in use.
+pub async fn verify_handler(
+    body: VerifyEmailBody,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    let verification = match nextchat_database::query(
+        "DELETE FROM email_verifications WHERE token = $1 RETURNING user_id, email, expires_at > CURRENT_TIMESTAMP AS valid",
+    )
+    .bind(hash_token(&body.token))
+    .fetch_one(&client)
+    .await
+    {
+        Ok(verification) if verification.get::<bool, _>("valid") => verification,
+        _ => {
+            return Ok(
+                Error::from_str("The verification link is invalid or has expired.")
+                    .to_response(400)
+                    .to_reply(),
+            );
+        }
+    };
+
+    let email: String = verification.get("email");
+
+    // The unique index only allows one account to verify each email.
+    match nextchat_database::query(
+        "UPDATE users SET email_verified_at = CURRENT_TIMESTAMP WHERE id = $1 AND email = $2 RETURNING id",
+    )
+    .bind(verification.get::<Uuid, _>("user_id"))
+    .bind(&email)
+    .fetch_optional(&client)
+    .await
+    {
+        Ok(Some(_)) => Ok(Response::new_success(EmailResponse {
+            email: Some(email),
+            email_verified: true,
+        })
+        .to_reply()),
+        Ok(None) => Ok(
+            Error::from_str("The verification link is invalid or has expired.")
+                .to_response(400)
+                .to_reply(),
+        ),
+        Err(_) => Ok(Error::from_str("The email is already in use.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
@@ -0,0 +1,303 @@
+//! NextChat Server export service module.
+//!
+//! This module contains the handler of the export controller route:
+//!
+//! `/users/export` -> export_handler
+
+use std::convert::Infallible;
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{
+        audit::AuditLogResponse,
+        blocks::BlockResponse,
+        export::*,
+        friends::FriendModel,
+        invites::InviteResponse,
+        passkeys::PasskeyResponse,
+        sessions::{SessionModel, SessionResponse},
+    },
+    Client, NaiveDateTime, Row, Uuid,
+};
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::authentication::Authentication,
+};
+
+/// Get the friendships of the user with the other user names.
+async fn get_friendships(
+    client: &Client,
+    user_id: &Uuid,
+) -> Result<Vec<ExportFriendship>, nextchat_database::Error> {
+    let friends = nextchat_database::query(
+        "SELECT friends.transmitter, friends.receiver, friends.state, friends.since, users.username FROM friends INNER JOIN users ON users.id = (CASE WHEN friends.transmitter = $1 THEN friends.receiver ELSE friends.transmitter END) WHERE friends.transmitter = $1 OR friends.receiver = $1 ORDER BY friends.since",
+    )
+    .bind(user_id)
+    .fetch_all(client)
+    .await?;
+
+    Ok(friends
+        .iter()
+        .map(|row| {
+            let friend = FriendModel::from_row(row);
+            let sent_by_me: bool = &friend.get_transmitter() == user_id;
+
+            ExportFriendship {
+                user_id: if sent_by_me {
+                    friend.get_receiver()
+                } else {
+                    friend.get_transmitter()
+                },
+                username: row.get("username"),
+                state: friend.get_state(),
+                sent_by_me,
+                since: friend.get_since(),
+            }
+        })
+        .collect())
+}
+
+/// Get the sessions of the user.
+async fn get_sessions(
+    client: &Client,
+    storage: &StorageType,
+    authentication: &Authentication,
+) -> Result<Vec<SessionResponse>, nextchat_database::Error> {
+    let sessions = nextchat_database::query(
+        "SELECT id, device_name, user_agent, ip, created_at, last_used FROM sessions WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(client)
+    .await?;
+
+    let storage = storage.read().await;
+
+    Ok(sessions
+        .iter()
+        .map(|row| {
+            let session = SessionModel::from_row(row);
+
+            SessionResponse {
+                id: session.get_id(),
+                device_name: session.get_device_name(),
+                user_agent: session.get_user_agent(),
+                ip: session.get_ip(),
+                created_at: session.get_created_at(),
+                last_used: session.get_last_used(),
+                connected: storage.is_connected(&session.get_id()),
+                current: session.get_id() == authentication.session_id,
+            }
+        })
+        .collect())
+}
+
+/// Get all data stored about the user.
+async fn get_export(
+    client: &Client,
+    storage: &StorageType,
+    authentication: &Authentication,
+    exported_at: NaiveDateTime,
+) -> Result<ExportResponse, nextchat_database::Error> {
+    let user = nextchat_database::query(
+        "SELECT id, username, email, email_verified_at, profile_image, totp_enabled, role, invited_by, banned_at, ban_reason, last_online, created_at, deletion_scheduled_at FROM users WHERE id = $1",
+    )
+    .bind(&authentication.user_id)
+    .fetch_one(client)
+    .await?;
+
+    let passkeys = nextchat_database::query(
+        "SELECT id, name, created_at, last_used FROM passkeys WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(client)
+    .await?;
+
+    let recovery_codes = nextchat_database::query(
+        "SELECT COUNT(code) AS count FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(&authentication.user_id)
+    .fetch_one(client)
+    .await?;
+
+    let blocks = nextchat_database::query(
+        "SELECT users.id, users.username, users.profile_image, blocks.created_at AS blocked_at FROM blocks INNER JOIN users ON users.id = blocks.blocked WHERE blocks.blocker = $1 ORDER BY blocks.created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(client)
+    .await?;
+
+    let invites = nextchat_database::query(
+        "SELECT code, max_uses, uses, expires_at, created_at FROM invites WHERE created_by = $1 ORDER BY created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(client)
+    .await?;
+
+    // The IP addresses of the other users that acted on the user are not exported.
+    let audit_log = nextchat_database::query(
+        "SELECT id, event, actor_id, target_id, (CASE WHEN actor_id IS NULL OR actor_id = $1 THEN ip END) AS ip, metadata, created_at FROM audit_log WHERE actor_id = $1 OR target_id = $1 ORDER BY created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(client)
+    .await?;
+
+    let friendships = get_friendships(client, &authentication.user_id).await?;
+    let sessions = get_sessions(client, storage, authentication).await?;
+
+    Ok(ExportResponse {
+        exported_at,
+        profile: ExportProfile {
+            id: user.get("id"),
+            username: user.get("username"),
+            email: user.get("email"),
+            email_verified_at: user.get("email_verified_at"),
+            profile_image: user.get("profile_image"),
+            totp_enabled: user.get("totp_enabled"),
+            role: user.get("role"),
+            invited_by: user.get("invited_by"),
+            banned_at: user.get("banned_at"),
+            ban_reason: user.get("ban_reason"),
+            last_online: user.get("last_online"),
+            created_at: user.get("created_at"),
+            deletion_scheduled_at: user.get("deletion_scheduled_at"),
+        },
+        friendships,
+        blocked_users: blocks.iter().map(BlockResponse::from_row).collect(),
+        sessions,
+        passkeys: passkeys
+            .iter()
+            .map(|passkey| PasskeyResponse::from_row(passkey))
+            .collect(),
+        recovery_codes_remaining: recovery_codes.get("count"),
+        invites: invites.iter().map(InviteResponse::from_row).collect(),
+        audit_log: audit_log.iter().map(AuditLogResponse::from_row).collect(),
+    })
+}
+
+/// `/users/export` handler.
+///
+/// Get a JSON file with all data stored about the user. The password hash, the
+/// TOTP secret and the recovery codes hashes are not exported, neither the IP
+/// addresses of the other users in the audit log events of the user.
+///
+/// # Response
+/// ```json
+/// {
+///     "exported_at": "2021-02-02T18:27:08",
+///     "profile": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "email": "contact@nextchat.org",
+///         "email_verified_at": "2021-02-02T18:27:08",
+///         "profile_image": "url.png",
+///         "totp_enabled": false,
+///         "role": "user",
+///         "invited_by": null,
+///         "banned_at": null,
+///         "ban_reason": null,
+///         "last_online": "2021-02-02T18:27:08",
+///         "created_at": "2021-02-02T18:27:08",
+///         "deletion_scheduled_at": null
+///     },
+///     "friendships": [
+///         {
+///             "user_id": "5959ad9c-598e-4deb-bcbe-053c1f73b400",
+///             "username": "danielsolartech",
+///             "state": "approved",
+///             "sent_by_me": true,
+///             "since": "2021-02-02T18:27:08"
+///         }
+///     ],
+///     "blocked_users": [ ... ],
+///     "sessions": [ ... ],
+///     "passkeys": [ ... ],
+///     "recovery_codes_remaining": 10,
+///     "invites": [ ... ],
+///     "audit_log": [
+///         {
+///             "id": 1,
+///             "event": "signin",
+///             "actor_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "target_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "ip": "127.0.0.1",
+///             "metadata": { "method": "password" },
+///             "created_at": "2021-02-02T18:27:08"
+///         }
+///     ]
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `429` - When the user already exported the data in the last 24 hours.
+///
+/// ## Errors
+/// 1. You can only export your data once a day.
+/// 2. Cannot export the data.
+pub async fn export_handler(
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    let mut transaction = match client.begin().await {
+        Ok(transaction) => transaction,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot export the data.")
+                .to_response(400)
+                .to_reply()
+                .into_response());
+        }
+    };
+
+    // The export is limited to one per day. The user row stays locked until the export
+    // ends, and the limit is not used if it fails.
+    let exported_at: NaiveDateTime = match nextchat_database::query(
+        "UPDATE users SET last_export_at = CURRENT_TIMESTAMP WHERE id = $1 AND (last_export_at IS NULL OR last_export_at <= CURRENT_TIMESTAMP - INTERVAL '1 day') RETURNING last_export_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_optional(&mut transaction)
+    .await
+    {
+        Ok(Some(user)) => user.get("last_export_at"),
+        Ok(None) => {
+            return Ok(Error::from_str("You can only export your data once a day.")
+                .to_response(429)
+                .to_reply()
+                .into_response());
+        }
+        Err(_) => {
+            return Ok(Error::from_str("Cannot export the data.")
+                .to_response(400)
+                .to_reply()
+                .into_response());
+        }
+    };
+
+    let export = match get_export(&client, &storage, &authentication, exported_at).await {
+        Ok(export) => export,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot export the data.")
+                .to_response(400)
+                .to_reply()
+                .into_response());
+        }
+    };
+
+    match transaction.commit().await {
+        Err(_) => Ok(Error::from_str("Cannot export the data.")
+            .to_response(400)
+            .to_reply()
+            .into_response()),
+        Ok(_) => {
+            let filename: String = format!("nextchat-{}.json", export.profile.username);
+
+            Ok(warp::reply::with_header(
+                Response::new_success(export).to_reply(),
+                "content-disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            )
+            .into_response())
+        }
+    }
+}
@@ -1,14 +1,85 @@
 //! NextChat Server friends service module.
+//!
+//! This module contains the handlers of the friends controller routes:
+//!
+//! `/friends/:user_one/:user_two`          -> are_friends_handler
+//! `/friends/get-of/:user_id`              -> get_of_handler
+//! `/friends/requests/incoming`            -> incoming_requests_handler
+//! `/friends/requests/outgoing`            -> outgoing_requests_handler
+//! `/friends/suggestions`                  -> suggestions_handler
+//! `/friends/requests/:user_id`            -> send_request_handler
+//! `/friends/requests/:user_id/accept`     -> accept_request_handler
+//! `/friends/requests/:user_id/decline`    -> decline_request_handler
+//! `/friends/requests/:user_id`            -> cancel_request_handler
+//! `/friends/:user_id`                     -> unfriend_handler
+//!
+//! And the handler of the `friend_request` event of the WebSocket connections, that
+//! shares the friend requests with `/friends/requests/:user_id`.
 
-use std::convert::Infallible;
+use std::{collections::HashSet, convert::Infallible};
 
-use nextchat_database::{models::friends::FriendModel, Client, NaiveDateTime, Uuid};
+use async_trait::async_trait;
+use nextchat_communication::{FriendRequestHandler, FriendRequestReceivedComposer, StorageType};
+use nextchat_database::{
+    models::{friends::*, users::UserDataResponse},
+    Client, NaiveDateTime, Row, Uuid,
+};
 use serde::Serialize;
 use warp::Reply;
 
-use crate::response::Response;
+use crate::{
+    response::{Error, Response},
+    services::{authentication::Authentication, email::can_send_friend_requests},
+};
 
-pub async fn get_friend_model_of(
+/// Errors of the friend requests, shared by `/friends/requests/:user_id` and the
+/// `friend_request` event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FriendRequestError {
+    SelfRequest,
+    EmailNotVerified,
+    UserNotFound,
+    Blocked,
+    AlreadyFriends,
+    AlreadySent,
+    AlreadyReceived,
+    Unknown,
+}
+
+impl FriendRequestError {
+    /// Get the message of the error sent by `/friends/requests/:user_id`.
+    pub fn get_message(&self) -> &'static str {
+        match self {
+            FriendRequestError::SelfRequest => "You cannot send a friend request to yourself.",
+            FriendRequestError::EmailNotVerified => {
+                "You must verify your email to send friend requests."
+            }
+            FriendRequestError::UserNotFound => "The user does not exist.",
+            FriendRequestError::Blocked => "You cannot send a friend request to this user.",
+            FriendRequestError::AlreadyFriends => "You are already friends.",
+            FriendRequestError::AlreadySent => "The friend request was already sent.",
+            FriendRequestError::AlreadyReceived => "The user already sent you a friend request.",
+            FriendRequestError::Unknown => "Cannot send the friend request.",
+        }
+    }
+
+    /// Get the code of the error sent by the `friend_request_result` packet.
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            FriendRequestError::SelfRequest => "self_request",
+            FriendRequestError::EmailNotVerified => "email_not_verified",
+            FriendRequestError::UserNotFound => "user_not_found",
+            FriendRequestError::Blocked => "blocked",
+            FriendRequestError::AlreadyFriends => "already_friends",
+            FriendRequestError::AlreadySent => "already_sent",
+            FriendRequestError::AlreadyReceived => "already_received",
+            FriendRequestError::Unknown => "unknown",
+        }
+    }
+}
+
+/// Get the friendship or request between two users, in either direction.
+async fn get_friend_model_of(
     client: &Client,
     user_one: &Uuid,
     user_two: &Uuid,
@@ -24,9 +95,140 @@ pub async fn get_friend_model_of(
     }
 }
 
+/// Send a friend request from the transmitter to the receiver.
+///
+/// There can only be one friendship or request between two users, in either
+/// direction.
+async fn send_friend_request(
+    client: &Client,
+    transmitter: &Uuid,
+    receiver: &Uuid,
+) -> Result<FriendModel, FriendRequestError> {
+    if transmitter == receiver {
+        return Err(FriendRequestError::SelfRequest);
+    }
+
+    if !can_send_friend_requests(client, transmitter).await {
+        return Err(FriendRequestError::EmailNotVerified);
+    }
+
+    // The banned users and the users scheduled for deletion cannot receive friend requests.
+    match nextchat_database::query(
+        "SELECT id FROM users WHERE id = $1 AND banned_at IS NULL AND deletion_scheduled_at IS NULL",
+    )
+    .bind(receiver)
+    .fetch_optional(client)
+    .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(FriendRequestError::UserNotFound),
+        Err(_) => return Err(FriendRequestError::Unknown),
+    }
+
+    // The blocked users cannot send friend requests to the blocker, and the
+    // blocker must unblock the user first.
+    match nextchat_database::query(
+        "SELECT blocker FROM blocks WHERE (blocker = $1 AND blocked = $2) OR (blocker = $2 AND blocked = $1)",
+    )
+    .bind(transmitter)
+    .bind(receiver)
+    .fetch_optional(client)
+    .await
+    {
+        Ok(None) => {}
+        Ok(Some(_)) => return Err(FriendRequestError::Blocked),
+        Err(_) => return Err(FriendRequestError::Unknown),
+    }
+
+    match get_friend_model_of(client, transmitter, receiver).await {
+        Err(nextchat_database::Error::RowNotFound) => {}
+        Err(_) => return Err(FriendRequestError::Unknown),
+        Ok(friend) if friend.get_state().is_approved() => {
+            return Err(FriendRequestError::AlreadyFriends);
+        }
+        Ok(friend) if friend.get_transmitter() == *transmitter => {
+            return Err(FriendRequestError::AlreadySent);
+        }
+        Ok(_) => return Err(FriendRequestError::AlreadyReceived),
+    }
+
+    // The pair index rejects the requests sent at the same time.
+    match nextchat_database::query(
+        "INSERT INTO friends(transmitter, receiver, state) VALUES ($1, $2, 'requested') ON CONFLICT DO NOTHING RETURNING transmitter, receiver, state, since",
+    )
+    .bind(transmitter)
+    .bind(receiver)
+    .fetch_optional(client)
+    .await
+    {
+        Ok(Some(friend)) => Ok(FriendModel::from_row(&friend)),
+        Ok(None) => Err(FriendRequestError::AlreadySent),
+        Err(_) => Err(FriendRequestError::Unknown),
+    }
+}
+
+/// Send the friend request received packet to the live connections of the
+/// receiver of a friend request.
+async fn notify_friend_request(client: &Client, storage: &StorageType, friend: &FriendModel) {
+    let connections = storage
+        .read()
+        .await
+        .get_user_connections(&friend.get_receiver());
+
+    if connections.is_empty() {
+        return;
+    }
+
+    let username: String =
+        match nextchat_database::query("SELECT username FROM users WHERE id = $1")
+            .bind(&friend.get_transmitter())
+            .fetch_one(client)
+            .await
+        {
+            Ok(user) => user.get("username"),
+            Err(_) => return,
+        };
+
+    for connection in connections.iter() {
+        connection
+            .send_packet(Box::new(FriendRequestReceivedComposer::new(
+                friend.get_transmitter().to_string(),
+                username.clone(),
+            )))
+            .ok();
+    }
+}
+
+/// Handler of the `friend_request` event of the WebSocket connections,
+/// registered in the storage on startup.
+pub struct FriendRequests;
+
+#[async_trait]
+impl FriendRequestHandler for FriendRequests {
+    async fn send_friend_request(
+        &self,
+        client: &Client,
+        storage: &StorageType,
+        transmitter: &Uuid,
+        receiver: &Uuid,
+    ) -> Result<(), String> {
+        match send_friend_request(client, transmitter, receiver).await {
+            Ok(friend) => {
+                notify_friend_request(client, storage, &friend).await;
+                Ok(())
+            }
+            Err(e) => Err(String::from(e.get_code())),
+        }
+    }
+}
+
+/// `/friends/:user_one/:user_two` handler.
+///
+/// The users are not friends when one of them blocked the authenticated user.
 pub async fn are_friends_handler(
     user_one: Uuid,
     user_two: Uuid,
+    authentication: Authentication,
     client: Client,
 ) -> Result<impl Reply, Infallible> {
     #[derive(Serialize)]
@@ -35,8 +237,27 @@ pub async fn are_friends_handler(
         pub since: Option<NaiveDateTime>,
     }
 
-    match get_friend_model_of(&client, &user_one, &user_two).await {
-        Err(_) => Ok(Response::new(
+    let blocked: bool = nextchat_database::query(
+        "SELECT blocker FROM blocks WHERE blocker IN ($1, $2) AND blocked = $3",
+    )
+    .bind(&user_one)
+    .bind(&user_two)
+    .bind(&authentication.user_id)
+    .fetch_optional(&client)
+    .await
+    .map(|block| block.is_some())
+    .unwrap_or(true);
+
+    let friend: Option<FriendModel> = if blocked {
+        None
+    } else {
+        get_friend_model_of(&client, &user_one, &user_two)
+            .await
+            .ok()
+    };
+
+    match friend {
+        None => Ok(Response::new(
             400,
             ResponseData {
                 are_friends: false,
@@ -44,7 +265,7 @@ pub async fn are_friends_handler(
             },
         )
         .to_reply()),
-        Ok(friend) => {
+        Some(friend) => {
             let are_friends = friend.get_state().is_approved();
             Ok(Response::new_success(ResponseData {
                 since: if are_friends {
@@ -58,3 +279,433 @@ pub async fn are_friends_handler(
         }
     }
 }
+
+/// `/friends/get-of/:user_id` handler.
+///
+/// # Request query
+/// - `?take={number}` _Default_ 10, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "user": {
+///             "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "username": "NextChat",
+///             "profile_image": "url.png"
+///         },
+///         "since": "2021-02-02T18:27:08",
+///         "connected": true
+///     }
+/// ]
+/// ```
+///
+/// The `connected` field is `true` when the friend has a live connection, it is
+/// always `false` in the friends of other users.
+///
+/// The list is empty when the user blocked the authenticated user, and the
+/// friends that blocked the authenticated user are not returned.
+///
+/// ## Status codes
+/// - `204` - When the user does not have friends.
+/// - `200` - When the user has one or more friends.
+///
+/// ## Errors
+/// 1. Cannot get the friends.
+pub async fn get_of_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    query: FriendsQuery,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT users.id, users.username, users.profile_image, friends.since FROM friends INNER JOIN users ON users.id = (CASE WHEN friends.transmitter = $1 THEN friends.receiver ELSE friends.transmitter END) WHERE (friends.transmitter = $1 OR friends.receiver = $1) AND friends.state = 'approved' AND users.deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT blocker FROM blocks WHERE blocker IN ($1, users.id) AND blocked = $4) ORDER BY friends.since DESC LIMIT $2 OFFSET $3",
+    )
+    .bind(&user_id)
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the friends.")
+            .to_response(400)
+            .to_reply()),
+        Ok(friends) => {
+            let connected_users: HashSet<Uuid> = if user_id == authentication.user_id {
+                storage.read().await.get_connected_users()
+            } else {
+                HashSet::new()
+            };
+
+            let friends: Vec<FriendResponse> = friends
+                .iter()
+                .map(|friend| {
+                    let user = UserDataResponse::from_row(friend);
+
+                    FriendResponse {
+                        connected: connected_users.contains(&user.id),
+                        since: friend.get("since"),
+                        user,
+                    }
+                })
+                .collect();
+
+            Ok(Response::new(if friends.is_empty() { 204 } else { 200 }, friends).to_reply())
+        }
+    }
+}
+
+/// Get the pending friend requests of the query and reply with `204` when there
+/// are none.
+async fn get_requests_reply(
+    client: &Client,
+    sql: &str,
+    user_id: &Uuid,
+    query: &FriendsQuery,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    match nextchat_database::query(sql)
+        .bind(user_id)
+        .bind(query.take.unwrap_or(10).clamp(1, 100))
+        .bind(query.skip.unwrap_or(0))
+        .fetch_all(client)
+        .await
+    {
+        Err(_) => Error::from_str("Cannot get the friend requests.")
+            .to_response(400)
+            .to_reply(),
+        Ok(requests) => {
+            let requests: Vec<FriendRequestResponse> = requests
+                .iter()
+                .map(FriendRequestResponse::from_row)
+                .collect();
+
+            Response::new(if requests.is_empty() { 204 } else { 200 }, requests).to_reply()
+        }
+    }
+}
+
+/// `/friends/requests/incoming` handler.
+///
+/// Get the pending friend requests sent to the user.
+///
+/// # Request query
+/// - `?take={number}` _Default_ 10, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "user": {
+///             "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "username": "NextChat",
+///             "profile_image": "url.png"
+///         },
+///         "since": "2021-02-02T18:27:08"
+///     }
+/// ]
+/// ```
+///
+/// ## Status codes
+/// - `204` - When there are no friend requests.
+/// - `200` - When there are one or more friend requests.
+///
+/// ## Errors
+/// 1. Cannot get the friend requests.
+pub async fn incoming_requests_handler(
+    authentication: Authentication,
+    query: FriendsQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    Ok(get_requests_reply(
+        &client,
+        "SELECT users.id, users.username, users.profile_image, friends.since FROM friends INNER JOIN users ON users.id = friends.transmitter WHERE friends.receiver = $1 AND friends.state = 'requested' AND users.deletion_scheduled_at IS NULL ORDER BY friends.since DESC LIMIT $2 OFFSET $3",
+        &authentication.user_id,
+        &query,
+    )
+    .await)
+}
+
+/// `/friends/requests/outgoing` handler.
+///
+/// Get the pending friend requests sent by the user, with the same response of
+/// `/friends/requests/incoming`.
+///
+/// ## Status codes
+/// - `204` - When there are no friend requests.
+/// - `200` - When there are one or more friend requests.
+///
+/// ## Errors
+/// 1. Cannot get the friend requests.
+pub async fn outgoing_requests_handler(
+    authentication: Authentication,
+    query: FriendsQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    Ok(get_requests_reply(
+        &client,
+        "SELECT users.id, users.username, users.profile_image, friends.since FROM friends INNER JOIN users ON users.id = friends.receiver WHERE friends.transmitter = $1 AND friends.state = 'requested' AND users.deletion_scheduled_at IS NULL ORDER BY friends.since DESC LIMIT $2 OFFSET $3",
+        &authentication.user_id,
+        &query,
+    )
+    .await)
+}
+
+/// `/friends/suggestions` handler.
+///
+/// Get the users that the user may know, ranked by the number of mutual friends.
+/// The friends, the users with a pending friend request and the blocked users in
+/// both directions are excluded, and the banned users and the users scheduled for
+/// deletion are neither suggested nor counted as mutual friends.
+///
+/// # Request query
+/// - `?take={number}` _Default_ 10, _Max_ 100
+/// - `?skip={number}` _Default_ 0
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "user": {
+///             "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "username": "NextChat",
+///             "profile_image": "url.png"
+///         },
+///         "mutual_friends": 4,
+///         "mutual_friend_names": ["Alice", "Bob", "Carol"]
+///     }
+/// ]
+/// ```
+///
+/// The `mutual_friend_names` field contains up to 3 mutual friends sorted by
+/// username.
+///
+/// ## Status codes
+/// - `204` - When there are no suggestions.
+/// - `200` - When there are one or more suggestions.
+///
+/// ## Errors
+/// 1. Cannot get the friend suggestions.
+pub async fn suggestions_handler(
+    authentication: Authentication,
+    query: FriendsQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "WITH my_friends AS (SELECT (CASE WHEN transmitter = $1 THEN receiver ELSE transmitter END) AS id FROM friends WHERE (transmitter = $1 OR receiver = $1) AND state = 'approved'), candidates AS (SELECT (CASE WHEN friends.transmitter = my_friends.id THEN friends.receiver ELSE friends.transmitter END) AS id, my_friends.id AS mutual_id FROM friends INNER JOIN my_friends ON my_friends.id IN (friends.transmitter, friends.receiver) WHERE friends.state = 'approved') SELECT users.id, users.username, users.profile_image, COUNT(*) AS mutual_friends, (ARRAY_AGG(mutual.username ORDER BY mutual.username))[1:3] AS mutual_friend_names FROM candidates INNER JOIN users ON users.id = candidates.id INNER JOIN users AS mutual ON mutual.id = candidates.mutual_id WHERE candidates.id <> $1 AND users.banned_at IS NULL AND users.deletion_scheduled_at IS NULL AND mutual.banned_at IS NULL AND mutual.deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT transmitter FROM friends WHERE (transmitter = $1 AND receiver = users.id) OR (transmitter = users.id AND receiver = $1)) AND NOT EXISTS (SELECT blocker FROM blocks WHERE (blocker = $1 AND blocked = users.id) OR (blocker = users.id AND blocked = $1)) GROUP BY users.id ORDER BY mutual_friends DESC, users.username LIMIT $2 OFFSET $3",
+    )
+    .bind(&authentication.user_id)
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the friend suggestions.")
+            .to_response(400)
+            .to_reply()),
+        Ok(suggestions) => {
+            let suggestions: Vec<FriendSuggestionResponse> =
+                suggestions.iter().map(FriendSuggestionResponse::from_row).collect();
+
+            Ok(Response::new(if suggestions.is_empty() { 204 } else { 200 }, suggestions)
+                .to_reply())
+        }
+    }
+}
+
+/// `/friends/requests/:user_id` handler.
+///
+/// Send a friend request to the user, the live connections of the user receive
+/// the `friend_request_received` packet.
+///
+/// # Response
+/// ```json
+/// {
+///     "user_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///     "state": "requested",
+///     "sent_by_me": true,
+///     "since": "2021-02-02T18:27:08"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `201` - When the friend request was sent.
+///
+/// ## Errors
+/// 1. You cannot send a friend request to yourself.
+/// 2. You must verify your email to send friend requests.
+/// 3. The user does not exist.
+/// 4. You cannot send a friend request to this user.
+/// 5. You are already friends.
+/// 6. The friend request was already sent.
+/// 7. The user already sent you a friend request.
+/// 8. Cannot send the friend request.
+pub async fn send_request_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    match send_friend_request(&client, &authentication.user_id, &user_id).await {
+        Ok(friend) => {
+            notify_friend_request(&client, &storage, &friend).await;
+
+            Ok(Response::new(
+                201,
+                FriendshipResponse::from_model(&friend, &authentication.user_id),
+            )
+            .to_reply())
+        }
+        Err(e) => Ok(Error::from_str(e.get_message()).to_response(400).to_reply()),
+    }
+}
+
+/// `/friends/requests/:user_id/accept` handler.
+///
+/// Accept the friend request sent by the user, the requests of the banned users and
+/// the users scheduled for deletion cannot be accepted.
+///
+/// # Response
+/// ```json
+/// {
+///     "user_id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///     "state": "approved",
+///     "sent_by_me": false,
+///     "since": "2021-02-02T18:27:08"
+/// }
+/// ```
+///
+/// ## Errors
+/// 1. Cannot accept the friend request.
+/// 2. The friend request does not exist.
+pub async fn accept_request_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "UPDATE friends SET state = 'approved', since = CURRENT_TIMESTAMP WHERE transmitter = $1 AND receiver = $2 AND state = 'requested' AND EXISTS (SELECT id FROM users WHERE id = $1 AND banned_at IS NULL AND deletion_scheduled_at IS NULL) RETURNING transmitter, receiver, state, since",
+    )
+    .bind(&user_id)
+    .bind(&authentication.user_id)
+    .fetch_optional(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot accept the friend request.")
+            .to_response(400)
+            .to_reply()),
+        Ok(None) => Ok(Error::from_str("The friend request does not exist.")
+            .to_response(404)
+            .to_reply()),
+        Ok(Some(friend)) => Ok(Response::new_success(FriendshipResponse::from_model(
+            &FriendModel::from_row(&friend),
+            &authentication.user_id,
+        ))
+        .to_reply()),
+    }
+}
+
+/// Delete the friendships of two users that match the query and reply with
+/// `204` or `404` when there are none.
+async fn delete_friendship_reply(
+    client: &Client,
+    query: &str,
+    user_one: &Uuid,
+    user_two: &Uuid,
+    error: &str,
+    not_found: &str,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    match nextchat_database::query(query)
+        .bind(user_one)
+        .bind(user_two)
+        .execute(client)
+        .await
+    {
+        Err(_) => Error::from_str(error).to_response(400).to_reply(),
+        Ok(result) if result.rows_affected() == 0 => {
+            Error::from_str(not_found).to_response(404).to_reply()
+        }
+        Ok(_) => Response::new(204, ()).to_reply(),
+    }
+}
+
+/// `/friends/requests/:user_id/decline` handler.
+///
+/// Decline the friend request sent by the user.
+///
+/// ## Status codes
+/// - `204` - When the friend request was declined.
+///
+/// ## Errors
+/// 1. Cannot decline the friend request.
+/// 2. The friend request does not exist.
+pub async fn decline_request_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    Ok(delete_friendship_reply(
+        &client,
+        "DELETE FROM friends WHERE transmitter = $1 AND receiver = $2 AND state = 'requested'",
+        &user_id,
+        &authentication.user_id,
+        "Cannot decline the friend request.",
+        "The friend request does not exist.",
+    )
+    .await)
+}
+
+/// `/friends/requests/:user_id` handler.
+///
+/// Cancel the friend request sent to the user.
+///
+/// ## Status codes
+/// - `204` - When the friend request was canceled.
+///
+/// ## Errors
+/// 1. Cannot cancel the friend request.
+/// 2. The friend request does not exist.
+pub async fn cancel_request_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    Ok(delete_friendship_reply(
+        &client,
+        "DELETE FROM friends WHERE transmitter = $1 AND receiver = $2 AND state = 'requested'",
+        &authentication.user_id,
+        &user_id,
+        "Cannot cancel the friend request.",
+        "The friend request does not exist.",
+    )
+    .await)
+}
+
+/// `/friends/:user_id` handler.
+///
+/// Remove the friendship with the user.
+///
+/// ## Status codes
+/// - `204` - When the friendship was removed.
+///
+/// ## Errors
+/// 1. Cannot remove the friend.
+/// 2. You are not friends with the user.
+pub async fn unfriend_handler(
+    user_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    Ok(delete_friendship_reply(
+        &client,
+        "DELETE FROM friends WHERE ((transmitter = $1 AND receiver = $2) OR (transmitter = $2 AND receiver = $1)) AND state = 'approved'",
+        &authentication.user_id,
+        &user_id,
+        "Cannot remove the friend.",
+        "You are not friends with the user.",
+    )
+    .await)
+}
@@ -0,0 +1,353 @@
+//! NextChat Server invites service module.
+//!
+//! This module contains the handlers of the invites controller routes:
+//!
+//! `/users/invites`        -> get_invites_handler
+//! `/users/invites`        -> create_invite_handler
+//! `/users/invites/:code`  -> delete_invite_handler
+//!
+//! The users can create invites until the number of users that signed up with
+//! them, plus the remaining uses of their active invites, reaches the quota. The
+//! users with the `ManageInvites` permission have no quota.
+//!
+//! # Environment variables
+//! - `SIGNUP_MODE` _Default_ `open` - `open` or `invite`, with `invite` the
+//!   `/users/signup` route requires an invite code.
+//! - `INVITES_PER_USER` _Default_ `5` - Quota of invite uses of each user.
+
+use std::{convert::Infallible, env};
+
+use nextchat_database::{
+    models::{
+        invites::*,
+        roles::{Permission, Role},
+    },
+    Client, Postgres, Row, Transaction, Uuid,
+};
+use nextchat_security::{format_recovery_code, generate_recovery_code};
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::authentication::Authentication,
+};
+
+/// Default quota of invite uses of each user.
+const DEFAULT_INVITES_PER_USER: i64 = 5;
+
+/// Default lifetime of the invites in days.
+const DEFAULT_INVITE_LIFETIME_DAYS: i32 = 7;
+
+/// Max uses of an invite.
+const MAX_INVITE_USES: i32 = 100;
+
+/// Max lifetime of the invites in days.
+const MAX_INVITE_LIFETIME_DAYS: i32 = 365;
+
+/// Query of the invite uses of a user, the users that signed up with the invites
+/// plus the remaining uses of the active invites.
+const USED_INVITES_QUERY: &str = "SELECT (SELECT COUNT(id) FROM users WHERE invited_by = $1) + (SELECT COALESCE(SUM(max_uses - uses), 0) FROM invites WHERE created_by = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)) AS used";
+
+/// Check if the `SIGNUP_MODE` environment variable requires an invite code to
+/// sign up.
+pub fn is_invite_only() -> bool {
+    env::var("SIGNUP_MODE")
+        .map(|mode| mode.trim().eq_ignore_ascii_case("invite"))
+        .unwrap_or(false)
+}
+
+/// Get the quota of invite uses of each user from the `INVITES_PER_USER`
+/// environment variable.
+fn get_invites_per_user() -> i64 {
+    env::var("INVITES_PER_USER")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|value| *value >= 0)
+        .unwrap_or(DEFAULT_INVITES_PER_USER)
+}
+
+/// Use an invite code in the signup transaction and get the user that created
+/// the invite.
+///
+/// The use is reverted when the transaction is not committed.
+pub async fn use_invite(
+    transaction: &mut Transaction<'_, Postgres>,
+    code: &str,
+) -> Result<Option<Uuid>, &'static str> {
+    match nextchat_database::query(
+        "UPDATE invites SET uses = uses + 1 WHERE code = $1 AND uses < max_uses AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP) RETURNING created_by",
+    )
+    .bind(code)
+    .fetch_optional(transaction)
+    .await
+    {
+        Ok(Some(invite)) => Ok(invite.get("created_by")),
+        Ok(None) => Err("The invite code is invalid or has expired."),
+        Err(_) => Err("Cannot use the invite code."),
+    }
+}
+
+/// Check if the user has the permission to manage the invites.
+async fn can_manage_invites(client: &Client, user_id: &Uuid) -> bool {
+    nextchat_database::query("SELECT role FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(client)
+        .await
+        .map(|user| {
+            user.get::<Role, _>("role")
+                .has_permission(Permission::ManageInvites)
+        })
+        .unwrap_or(false)
+}
+
+/// Get the remaining invite uses of the user, `None` when the user has no quota.
+async fn get_remaining_uses(
+    client: &Client,
+    user_id: &Uuid,
+) -> Result<Option<i64>, nextchat_database::Error> {
+    if can_manage_invites(client, user_id).await {
+        return Ok(None);
+    }
+
+    let used: i64 = nextchat_database::query(USED_INVITES_QUERY)
+        .bind(user_id)
+        .fetch_one(client)
+        .await?
+        .get("used");
+
+    Ok(Some((get_invites_per_user() - used).max(0)))
+}
+
+/// Get the remaining invite uses of the user in a transaction, `None` when the
+/// user has no quota.
+///
+/// The row of the user is locked until the end of the transaction, so the
+/// invites created at the same time are checked against the quota one by one.
+async fn lock_remaining_uses(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> Result<Option<i64>, nextchat_database::Error> {
+    let role: Role = nextchat_database::query("SELECT role FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_one(&mut *transaction)
+        .await?
+        .get("role");
+
+    if role.has_permission(Permission::ManageInvites) {
+        return Ok(None);
+    }
+
+    let used: i64 = nextchat_database::query(USED_INVITES_QUERY)
+        .bind(user_id)
+        .fetch_one(&mut *transaction)
+        .await?
+        .get("used");
+
+    Ok(Some((get_invites_per_user() - used).max(0)))
+}
+
+/// `/users/invites` handler.
+///
+/// # Response
+/// ```json
+/// {
+///     "invites": [
+///         {
+///             "code": "k2vd-q7xm-a4np-3hse",
+///             "max_uses": 1,
+///             "uses": 0,
+///             "expires_at": "2021-02-09T18:27:08",
+///             "created_at": "2021-02-02T18:27:08"
+///         }
+///     ],
+///     "invited_users": [
+///         {
+///             "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///             "username": "NextChat",
+///             "created_at": "2021-02-02T18:27:08"
+///         }
+///     ],
+///     "remaining_uses": 4
+/// }
+/// ```
+///
+/// The `remaining_uses` is `null` when the user has no quota.
+///
+/// ## Errors
+/// 1. Cannot get the invites.
+pub async fn get_invites_handler(
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    let invites = nextchat_database::query(
+        "SELECT code, max_uses, uses, expires_at, created_at FROM invites WHERE created_by = $1 ORDER BY created_at DESC",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await;
+
+    let invited_users = nextchat_database::query(
+        "SELECT id, username, created_at FROM users WHERE invited_by = $1 ORDER BY created_at DESC",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await;
+
+    let remaining_uses = get_remaining_uses(&client, &authentication.user_id).await;
+
+    match (invites, invited_users, remaining_uses) {
+        (Ok(invites), Ok(invited_users), Ok(remaining_uses)) => {
+            Ok(Response::new_success(InvitesResponse {
+                invites: invites.iter().map(InviteResponse::from_row).collect(),
+                invited_users: invited_users
+                    .iter()
+                    .map(InvitedUserResponse::from_row)
+                    .collect(),
+                remaining_uses,
+            })
+            .to_reply())
+        }
+        _ => Ok(Error::from_str("Cannot get the invites.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
+
+/// `/users/invites` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "max_uses": 1,
+///     "expires_in_days": 7
+/// }
+/// ```
+///
+/// ## Requeriments
+/// - `max_uses` _Optional_ - Default: 1 - Min: 1 - Max: 100
+/// - `expires_in_days` _Optional_ - Default: 7 - Min: 1 - Max: 365
+///
+/// # Response
+/// ```json
+/// {
+///     "code": "k2vd-q7xm-a4np-3hse",
+///     "max_uses": 1,
+///     "uses": 0,
+///     "expires_at": "2021-02-09T18:27:08",
+///     "created_at": "2021-02-02T18:27:08"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `201` - When the invite was created.
+/// - `403` - When the invite exceeds the quota of the user.
+///
+/// ## Errors
+/// 1. The max uses must be between 1 and 100.
+/// 2. The expiration must be between 1 and 365 days.
+/// 3. Cannot get the invites.
+/// 4. You do not have enough invites left.
+/// 5. Cannot create the invite.
+pub async fn create_invite_handler(
+    authentication: Authentication,
+    body: CreateInviteBody,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    let max_uses: i32 = body.max_uses.unwrap_or(1);
+    if !(1..=MAX_INVITE_USES).contains(&max_uses) {
+        return Ok(Error::from_str("The max uses must be between 1 and 100.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    let expires_in_days: i32 = body.expires_in_days.unwrap_or(DEFAULT_INVITE_LIFETIME_DAYS);
+    if !(1..=MAX_INVITE_LIFETIME_DAYS).contains(&expires_in_days) {
+        return Ok(
+            Error::from_str("The expiration must be between 1 and 365 days.")
+                .to_response(400)
+                .to_reply(),
+        );
+    }
+
+    let mut transaction = match client.begin().await {
+        Ok(transaction) => transaction,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot create the invite.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    match lock_remaining_uses(&mut transaction, &authentication.user_id).await {
+        Err(_) => {
+            return Ok(Error::from_str("Cannot get the invites.")
+                .to_response(400)
+                .to_reply());
+        }
+        Ok(Some(remaining_uses)) if remaining_uses < i64::from(max_uses) => {
+            return Ok(Error::from_str("You do not have enough invites left.")
+                .to_response(403)
+                .to_reply());
+        }
+        Ok(_) => {}
+    }
+
+    match nextchat_database::query(
+        "INSERT INTO invites(code, created_by, max_uses, expires_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(days => $4)) RETURNING code, max_uses, uses, expires_at, created_at",
+    )
+    .bind(generate_recovery_code())
+    .bind(&authentication.user_id)
+    .bind(max_uses)
+    .bind(expires_in_days)
+    .fetch_one(&mut transaction)
+    .await
+    {
+        Ok(invite) if transaction.commit().await.is_ok() => {
+            Ok(Response::new(201, InviteResponse::from_row(&invite)).to_reply())
+        }
+        _ => Ok(Error::from_str("Cannot create the invite.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
+
+/// `/users/invites/:code` handler.
+///
+/// The users can only remove their invites, except the users with the
+/// `ManageInvites` permission. The users that signed up with the invite are
+/// not affected.
+///
+/// ## Status codes
+/// - `204` - When the invite was removed.
+///
+/// ## Errors
+/// 1. Cannot remove the invite.
+/// 2. The invite {code} does not exist.
+pub async fn delete_invite_handler(
+    code: String,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    let code: String = format_recovery_code(&code);
+    let any_invite: bool = can_manage_invites(&client, &authentication.user_id).await;
+
+    match nextchat_database::query(
+        "DELETE FROM invites WHERE code = $1 AND ($2 OR created_by = $3)",
+    )
+    .bind(&code)
+    .bind(any_invite)
+    .bind(&authentication.user_id)
+    .execute(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot remove the invite.")
+            .to_response(400)
+            .to_reply()),
+        Ok(result) if result.rows_affected() == 0 => {
+            Ok(Error::new(format!("The invite {} does not exist.", code))
+                .to_response(404)
+                .to_reply())
+        }
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
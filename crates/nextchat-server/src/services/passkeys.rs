@@ -0,0 +1,480 @@
+//! NextChat Server passkeys service module.
+//!
+//! This module contains the handlers of the passkeys controller routes:
+//!
+//! `/users/passkeys`                   -> get_passkeys_handler
+//! `/users/passkeys/:passkey_id`       -> delete_passkey_handler
+//! `/users/passkeys/register/start`    -> register_start_handler
+//! `/users/passkeys/register/finish`   -> register_finish_handler
+//! `/users/signin/passkey/start`       -> signin_start_handler
+//! `/users/signin/passkey/finish`      -> signin_finish_handler
+
+use std::{convert::Infallible, sync::Arc};
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{audit::AuditEvent, passkeys::*, users::*},
+    Client, Row, Uuid,
+};
+use nextchat_security::{
+    get_credential_id, Credential, Passkeys, PublicKeyCredential, RegisterPublicKeyCredential,
+};
+use nextchat_utils::normalize_username;
+use serde::Serialize;
+use serde_json::json;
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::{
+        audit::record_event,
+        authentication::Authentication,
+        two_factor,
+        users::{authentication_reply, confirm_password, get_lock_time},
+    },
+};
+
+/// Max length of the passkey name.
+const PASSKEY_NAME_MAX_LENGTH: usize = 50;
+
+pub type PasskeysType = Arc<Passkeys>;
+
+/// Get the challenge response of a ceremony.
+fn challenge_reply<T: Serialize>(
+    ceremony_id: String,
+    options: T,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    match serde_json::to_value(options) {
+        Ok(options) => Response::new_success(PasskeyChallengeResponse {
+            ceremony_id,
+            options,
+        })
+        .to_reply(),
+        Err(_) => Error::from_str("Cannot start the ceremony.")
+            .to_response(400)
+            .to_reply(),
+    }
+}
+
+/// `/users/passkeys` handler.
+///
+/// # Response
+/// ```json
+/// [
+///     {
+///         "id": "0b7c5e0e-2f7d-4a4b-8d1e-8e4c3f0a9b12",
+///         "name": "YubiKey",
+///         "created_at": "2021-02-02T18:27:08",
+///         "last_used": "2021-02-02T18:27:08"
+///     }
+/// ]
+/// ```
+///
+/// ## Errors
+/// 1. Cannot get the passkeys.
+pub async fn get_passkeys_handler(
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query(
+        "SELECT id, name, created_at, last_used FROM passkeys WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot get the passkeys.")
+            .to_response(400)
+            .to_reply()),
+        Ok(passkeys) => {
+            let passkeys: Vec<PasskeyResponse> = passkeys
+                .iter()
+                .map(|passkey| PasskeyResponse::from_row(passkey))
+                .collect();
+
+            Ok(Response::new_success(passkeys).to_reply())
+        }
+    }
+}
+
+/// `/users/passkeys/:passkey_id` handler.
+///
+/// ## Status codes
+/// - `204` - When the passkey was removed.
+///
+/// ## Errors
+/// 1. Cannot remove the passkey.
+/// 2. The passkey #{passkey_id} does not exist.
+pub async fn delete_passkey_handler(
+    passkey_id: Uuid,
+    authentication: Authentication,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    match nextchat_database::query("DELETE FROM passkeys WHERE id = $1 AND user_id = $2")
+        .bind(&passkey_id)
+        .bind(&authentication.user_id)
+        .execute(&client)
+        .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot remove the passkey.")
+            .to_response(400)
+            .to_reply()),
+        Ok(result) if result.rows_affected() == 0 => Ok(Error::new(format!(
+            "The passkey #{} does not exist.",
+            passkey_id
+        ))
+        .to_response(404)
+        .to_reply()),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/users/passkeys/register/start` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "options": {
+///         "publicKey": { "challenge": "...", "rp": { ... }, "user": { ... }, ... }
+///     }
+/// }
+/// ```
+///
+/// The options must be passed to `navigator.credentials.create()`.
+///
+/// ## Status codes
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. Cannot start the passkey registration.
+/// 3. Too many ceremonies in progress, try again later.
+/// 4. Too many failed attempts, try again in {seconds} seconds.
+pub async fn register_start_handler(
+    authentication: Authentication,
+    body: PasswordConfirmationBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+    passkeys: PasskeysType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    let username: String =
+        match nextchat_database::query("SELECT username FROM users WHERE id = $1")
+            .bind(&authentication.user_id)
+            .fetch_one(&client)
+            .await
+        {
+            Ok(user) => user.get("username"),
+            Err(_) => {
+                return Ok(Error::from_str("Cannot start the passkey registration.")
+                    .to_response(400)
+                    .to_reply());
+            }
+        };
+
+    match passkeys.start_registration(&authentication.user_id.to_string(), &ip, &username) {
+        Ok((ceremony_id, options)) => Ok(challenge_reply(ceremony_id, options)),
+        Err(e) => Ok(Error::new(e).to_response(400).to_reply()),
+    }
+}
+
+/// `/users/passkeys/register/finish` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "name": "YubiKey",
+///     "credential": { "id": "...", "rawId": "...", "response": { ... }, "type": "public-key" }
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "id": "0b7c5e0e-2f7d-4a4b-8d1e-8e4c3f0a9b12",
+///     "name": "YubiKey",
+///     "created_at": "2021-02-02T18:27:08",
+///     "last_used": "2021-02-02T18:27:08"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `201` - When the passkey was registered.
+///
+/// ## Errors
+/// 1. The credential is invalid.
+/// 2. The ceremony is invalid or has expired.
+/// 3. Cannot verify the passkey.
+/// 4. Cannot register the passkey.
+pub async fn register_finish_handler(
+    authentication: Authentication,
+    body: FinishPasskeyRegistrationBody,
+    client: Client,
+    passkeys: PasskeysType,
+) -> Result<impl Reply, Infallible> {
+    let registration: RegisterPublicKeyCredential = match serde_json::from_value(body.credential) {
+        Ok(registration) => registration,
+        Err(_) => {
+            return Ok(Error::from_str("The credential is invalid.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    let credential: Credential = match passkeys.finish_registration(
+        &body.ceremony_id,
+        &authentication.user_id.to_string(),
+        &registration,
+    ) {
+        Ok(credential) => credential,
+        Err(e) => {
+            return Ok(Error::new(e).to_response(400).to_reply());
+        }
+    };
+
+    let name: String = match body.name {
+        Some(name) if !name.trim().is_empty() => {
+            name.trim().chars().take(PASSKEY_NAME_MAX_LENGTH).collect()
+        }
+        _ => String::from("Passkey"),
+    };
+
+    let serialized: String = match serde_json::to_string(&credential) {
+        Ok(serialized) => serialized,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot register the passkey.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    // The credential id is unique, so a passkey cannot be registered twice.
+    match nextchat_database::query(
+        "INSERT INTO passkeys(user_id, credential_id, credential, name) VALUES ($1, $2, $3, $4) RETURNING id, name, created_at, last_used",
+    )
+    .bind(&authentication.user_id)
+    .bind(get_credential_id(&credential))
+    .bind(&serialized)
+    .bind(&name)
+    .fetch_one(&client)
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot register the passkey.")
+            .to_response(400)
+            .to_reply()),
+        Ok(passkey) => Ok(Response::new(201, PasskeyResponse::from_row(&passkey)).to_reply()),
+    }
+}
+
+/// `/users/signin/passkey/start` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "username": "NextChat"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "options": {
+///         "publicKey": { "challenge": "...", "allowCredentials": [ ... ], ... }
+///     }
+/// }
+/// ```
+///
+/// The options must be passed to `navigator.credentials.get()`.
+///
+/// ## Status codes
+/// - `429` - When the account or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. Too many failed attempts, try again in {seconds} seconds.
+/// 2. Cannot sign in with a passkey.
+pub async fn signin_start_handler(
+    body: StartPasskeySignInBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+    passkeys: PasskeysType,
+) -> Result<impl Reply, Infallible> {
+    let username: String = normalize_username(&body.username);
+    let account_key: String = username.to_lowercase();
+
+    // Check if the account or the IP address are locked.
+    if let Some(lock_time) = get_lock_time(&storage, &account_key, &ip).await {
+        return Ok(Error::new(format!(
+            "Too many failed attempts, try again in {} seconds.",
+            lock_time.as_secs() + 1
+        ))
+        .to_response(429)
+        .to_reply());
+    }
+
+    let rows = nextchat_database::query(
+        "SELECT passkeys.user_id, passkeys.credential FROM passkeys INNER JOIN users ON users.id = passkeys.user_id WHERE LOWER(users.username) = LOWER($1)",
+    )
+    .bind(&username)
+    .fetch_all(&client)
+    .await
+    .unwrap_or_default();
+
+    let user_id: Option<Uuid> = rows.first().map(|row| row.get("user_id"));
+    let credentials: Vec<Credential> = rows
+        .iter()
+        .filter_map(|row| serde_json::from_str(row.get("credential")).ok())
+        .collect();
+
+    // The same error is returned for unknown users, users without passkeys and the
+    // IP addresses with too many ceremonies in progress.
+    match user_id
+        .map(|user_id| passkeys.start_authentication(&user_id.to_string(), &ip, credentials))
+    {
+        Some(Ok((ceremony_id, options))) => Ok(challenge_reply(ceremony_id, options)),
+        _ => Ok(Error::from_str("Cannot sign in with a passkey.")
+            .to_response(400)
+            .to_reply()),
+    }
+}
+
+/// `/users/signin/passkey/finish` handler.
+///
+/// # Request body
+/// ```json
+/// {
+///     "ceremony_id": "Q2hhbGxlbmdl...",
+///     "credential": { "id": "...", "rawId": "...", "response": { ... }, "type": "public-key" },
+///     "device_name": "NextChat Desktop"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "user": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "profile_image": "url.png"
+///     },
+///     "access_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "refresh_token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+///
+/// When the user has the two-factor authentication enabled, the response is the
+/// challenge of `/users/signin/2fa`, the same as `/users/signin`.
+///
+/// ## Errors
+/// 1. The credential is invalid.
+/// 2. The ceremony is invalid or has expired.
+/// 3. Cannot verify the passkey.
+/// 4. Cannot create the session.
+pub async fn signin_finish_handler(
+    body: FinishPasskeySignInBody,
+    ip: String,
+    user_agent: String,
+    client: Client,
+    storage: StorageType,
+    passkeys: PasskeysType,
+) -> Result<impl Reply, Infallible> {
+    let assertion: PublicKeyCredential = match serde_json::from_value(body.credential) {
+        Ok(assertion) => assertion,
+        Err(_) => {
+            return Ok(Error::from_str("The credential is invalid.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    let (owner, credential_id, counter) =
+        match passkeys.finish_authentication(&body.ceremony_id, &assertion) {
+            Ok(result) => result,
+            Err(e) => {
+                storage.write().await.get_ip_attempts().add_failure(&ip);
+
+                record_event(
+                    &client,
+                    AuditEvent::SigninFailed,
+                    None,
+                    None,
+                    Some(&ip),
+                    json!({ "method": "passkey", "reason": "passkey" }),
+                )
+                .await;
+
+                return Ok(Error::new(e).to_response(400).to_reply());
+            }
+        };
+    let user_id: Uuid = Uuid::parse_str(&owner).expect("Cannot parse the ceremony owner.");
+
+    let passkey = match nextchat_database::query(
+        "SELECT passkeys.credential, users.id, users.username, users.profile_image, users.totp_enabled FROM passkeys INNER JOIN users ON users.id = passkeys.user_id WHERE passkeys.credential_id = $1 AND users.id = $2",
+    )
+    .bind(&credential_id)
+    .bind(&user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(passkey) => passkey,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot verify the passkey.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    // Store the new signature counter to detect cloned authenticators.
+    if let Ok(mut credential) = serde_json::from_str::<Credential>(passkey.get("credential")) {
+        credential.counter = counter;
+
+        if let Ok(serialized) = serde_json::to_string(&credential) {
+            let _ = nextchat_database::query(
+                "UPDATE passkeys SET credential = $1, last_used = CURRENT_TIMESTAMP WHERE credential_id = $2",
+            )
+            .bind(&serialized)
+            .bind(&credential_id)
+            .execute(&client)
+            .await;
+        }
+    }
+
+    // The session is created after verifying the second factor.
+    if passkey.get::<bool, _>("totp_enabled") {
+        return Ok(two_factor::challenge_reply(&user_id));
+    }
+
+    Ok(authentication_reply(
+        &client,
+        UserDataResponse::from_row(&passkey),
+        None,
+        body.device_name,
+        &user_agent,
+        &ip,
+        AuditEvent::Signin,
+        "passkey",
+    )
+    .await)
+}
@@ -0,0 +1,155 @@
+//! NextChat Server proof of work service module.
+//!
+//! This module contains the functions to verify the proof of work of the signups
+//! and the handler of the signup challenge route:
+//!
+//! `/users/signup/challenge` -> signup_challenge_handler
+//!
+//! The challenges are signed tokens with a random nonce and the difficulty, so
+//! they are not stored until they are used. The difficulty of the new challenges
+//! is raised when the signups of the last minutes exceed `POW_SIGNUP_THRESHOLD`,
+//! see `nextchat_security::ProofOfWork`.
+
+use std::convert::Infallible;
+
+use nextchat_database::{
+    models::users::SignUpChallengeResponse, Client, Postgres, Row, Transaction,
+};
+use nextchat_security::{
+    decode_token, encode_token, generate_random_token, get_timestamp, hash_token,
+    verify_proof_of_work, ProofOfWork,
+};
+use serde::{Deserialize, Serialize};
+use warp::Reply;
+
+use crate::response::{Error, Response};
+
+/// Lifetime of the challenges in seconds (5 minutes).
+const CHALLENGE_LIFETIME: u64 = 60 * 5;
+
+const CHALLENGE_PURPOSE: &str = "signup";
+
+/// Window of the signup rate in minutes.
+const SIGNUP_RATE_WINDOW_MINUTES: i32 = 10;
+
+#[derive(Serialize, Deserialize)]
+struct ChallengeClaims {
+    nonce: String,
+    difficulty: u8,
+    purpose: String,
+    exp: u64,
+}
+
+/// Get the number of users that signed up in the rate window.
+async fn get_recent_signups(client: &Client) -> u64 {
+    nextchat_database::query(
+        "SELECT COUNT(id) AS count FROM users WHERE created_at > CURRENT_TIMESTAMP - make_interval(mins => $1)",
+    )
+    .bind(SIGNUP_RATE_WINDOW_MINUTES)
+    .fetch_one(client)
+    .await
+    .map(|row| row.get::<i64, _>("count") as u64)
+    .unwrap_or(0)
+}
+
+/// Verify the solution of a signup challenge and get the nonce of the challenge,
+/// the challenge is marked as used with `use_signup_challenge`.
+///
+/// The signups do not require a solution when the proof of work is disabled.
+pub fn verify_signup_challenge(
+    challenge: Option<&str>,
+    solution: Option<&str>,
+) -> Result<Option<String>, &'static str> {
+    if !ProofOfWork::from_env().is_enabled() {
+        return Ok(None);
+    }
+
+    let (challenge, solution) = match (challenge, solution) {
+        (Some(challenge), Some(solution)) => (challenge, solution),
+        _ => return Err("You must solve the proof of work challenge."),
+    };
+
+    let claims = match decode_token::<ChallengeClaims>(challenge) {
+        Ok(claims) if claims.purpose == CHALLENGE_PURPOSE => claims,
+        _ => return Err("The challenge is invalid or has expired."),
+    };
+
+    if !verify_proof_of_work(challenge, solution, claims.difficulty) {
+        return Err("The proof of work solution is incorrect.");
+    }
+
+    Ok(Some(claims.nonce))
+}
+
+/// Mark a signup challenge as used in the transaction of the signup, so the
+/// challenge is only used when the user is created.
+pub async fn use_signup_challenge(
+    transaction: &mut Transaction<'_, Postgres>,
+    nonce: &str,
+) -> Result<(), &'static str> {
+    match nextchat_database::query(
+        "INSERT INTO proof_of_work_challenges(nonce, expires_at) VALUES ($1, CURRENT_TIMESTAMP + make_interval(secs => $2)) ON CONFLICT DO NOTHING",
+    )
+    .bind(hash_token(nonce))
+    .bind(CHALLENGE_LIFETIME as f64)
+    .execute(transaction)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => Ok(()),
+        Ok(_) => Err("The challenge was already used."),
+        Err(_) => Err("Cannot verify the challenge."),
+    }
+}
+
+/// Delete the used challenges that have expired.
+pub async fn purge_used_challenges(client: &Client) {
+    if let Err(e) = nextchat_database::query(
+        "DELETE FROM proof_of_work_challenges WHERE expires_at < CURRENT_TIMESTAMP",
+    )
+    .execute(client)
+    .await
+    {
+        eprintln!("Cannot delete the used challenges: {:?}", e);
+    }
+}
+
+/// `/users/signup/challenge` handler.
+///
+/// # Response
+/// ```json
+/// {
+///     "challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "difficulty": 20,
+///     "expires_in": 300
+/// }
+/// ```
+///
+/// The solution is a string whose SHA-256 hash of `{challenge}:{solution}`
+/// starts with `difficulty` zero bits. The difficulty is `0` when the proof of
+/// work is disabled.
+///
+/// ## Errors
+/// 1. Cannot sign the token.
+pub async fn signup_challenge_handler(client: Client) -> Result<impl Reply, Infallible> {
+    let proof_of_work = ProofOfWork::from_env();
+    let difficulty: u8 = if proof_of_work.is_enabled() {
+        proof_of_work.get_difficulty(get_recent_signups(&client).await)
+    } else {
+        0
+    };
+
+    match encode_token(&ChallengeClaims {
+        nonce: generate_random_token(),
+        difficulty,
+        purpose: String::from(CHALLENGE_PURPOSE),
+        exp: get_timestamp() + CHALLENGE_LIFETIME,
+    }) {
+        Ok(challenge) => Ok(Response::new_success(SignUpChallengeResponse {
+            challenge,
+            difficulty,
+            expires_in: CHALLENGE_LIFETIME,
+        })
+        .to_reply()),
+        Err(e) => Ok(Error::new(e).to_response(400).to_reply()),
+    }
+}
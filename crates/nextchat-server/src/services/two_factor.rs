@@ -0,0 +1,512 @@
+//! NextChat Server two-factor authentication service module.
+//!
+//! This module contains the functions to verify the TOTP codes of the users and
+//! the handlers of the two-factor authentication controller routes:
+//!
+//! `/users/2fa/enroll`     -> enroll_handler
+//! `/users/2fa/confirm`    -> confirm_handler
+//! `/users/2fa/disable`    -> disable_handler
+//! `/users/signin/2fa`     -> second_factor_handler
+
+use std::convert::Infallible;
+
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    models::{audit::AuditEvent, two_factor::*, users::*},
+    Client, Row, Uuid,
+};
+use nextchat_security::{
+    decode_token, decrypt_secret, encode_token, encrypt_secret, generate_totp_secret,
+    get_timestamp, get_totp_uri, verify_totp_code,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use warp::Reply;
+
+use crate::{
+    response::{Error, Response},
+    services::{
+        audit::record_event,
+        authentication::Authentication,
+        users::{add_failed_attempt, authentication_reply, confirm_password, get_lock_time},
+    },
+};
+
+/// Issuer shown by the authenticator apps.
+const TOTP_ISSUER: &str = "NextChat";
+
+/// Lifetime of the second factor challenges in seconds (5 minutes).
+const CHALLENGE_LIFETIME: u64 = 60 * 5;
+
+/// Purpose of the second factor challenge tokens.
+const CHALLENGE_PURPOSE: &str = "second_factor";
+
+#[derive(Serialize, Deserialize)]
+struct ChallengeClaims {
+    sub: Uuid,
+    purpose: String,
+    exp: u64,
+}
+
+/// Get the second factor challenge response for a user whose password was verified.
+pub fn challenge_reply(user_id: &Uuid) -> warp::reply::WithStatus<warp::reply::Json> {
+    match encode_token(&ChallengeClaims {
+        sub: *user_id,
+        purpose: String::from(CHALLENGE_PURPOSE),
+        exp: get_timestamp() + CHALLENGE_LIFETIME,
+    }) {
+        Ok(challenge) => Response::new_success(SecondFactorChallengeResponse {
+            second_factor_required: true,
+            challenge,
+        })
+        .to_reply(),
+        Err(e) => Error::new(e).to_response(400).to_reply(),
+    }
+}
+
+/// Verify a TOTP code of the user and store its step, so the same code cannot be
+/// used again.
+async fn verify_code(
+    client: &Client,
+    user_id: &Uuid,
+    encrypted_secret: &str,
+    last_step: i64,
+    code: &str,
+) -> bool {
+    let secret: String = match decrypt_secret(encrypted_secret) {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!(
+                "Cannot decrypt the TOTP secret of the user #{}: {}",
+                user_id, e
+            );
+            return false;
+        }
+    };
+
+    let step: i64 = match verify_totp_code(&secret, code, get_timestamp()) {
+        Some(step) if step as i64 > last_step => step as i64,
+        _ => {
+            return false;
+        }
+    };
+
+    match nextchat_database::query(
+        "UPDATE users SET totp_last_step = $1 WHERE id = $2 AND totp_last_step < $1",
+    )
+    .bind(step)
+    .bind(user_id)
+    .execute(client)
+    .await
+    {
+        Ok(result) => result.rows_affected() == 1,
+        Err(_) => false,
+    }
+}
+
+/// `/users/2fa/enroll` handler.
+///
+/// Generate a new TOTP secret for the user. The two-factor authentication is not
+/// enabled until a code is confirmed with `/users/2fa/confirm`.
+///
+/// # Request body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "secret": "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP",
+///     "uri": "otpauth://totp/NextChat:NextChat?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=NextChat&algorithm=SHA1&digits=6&period=30"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. The two-factor authentication is already enabled.
+/// 3. Cannot encrypt the secret.
+/// 4. Cannot enroll the two-factor authentication.
+/// 5. Too many failed attempts, try again in {seconds} seconds.
+pub async fn enroll_handler(
+    authentication: Authentication,
+    body: PasswordConfirmationBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    let user =
+        match nextchat_database::query("SELECT username, totp_enabled FROM users WHERE id = $1")
+            .bind(&authentication.user_id)
+            .fetch_one(&client)
+            .await
+        {
+            Ok(user) => user,
+            Err(_) => {
+                return Ok(
+                    Error::from_str("Cannot enroll the two-factor authentication.")
+                        .to_response(400)
+                        .to_reply(),
+                );
+            }
+        };
+
+    if user.get::<bool, _>("totp_enabled") {
+        return Ok(
+            Error::from_str("The two-factor authentication is already enabled.")
+                .to_response(400)
+                .to_reply(),
+        );
+    }
+
+    let secret: String = generate_totp_secret();
+    let encrypted_secret: String = match encrypt_secret(&secret) {
+        Ok(encrypted_secret) => encrypted_secret,
+        Err(e) => {
+            return Ok(Error::new(e).to_response(400).to_reply());
+        }
+    };
+
+    match nextchat_database::query(
+        "UPDATE users SET totp_secret = $1, totp_last_step = 0 WHERE id = $2 AND totp_enabled = false",
+    )
+    .bind(&encrypted_secret)
+    .bind(&authentication.user_id)
+    .execute(&client)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => {
+            let username: String = user.get("username");
+            let uri: String = get_totp_uri(&secret, TOTP_ISSUER, &username);
+
+            Ok(Response::new_success(TotpEnrollmentResponse { secret, uri }).to_reply())
+        }
+        _ => Ok(
+            Error::from_str("Cannot enroll the two-factor authentication.")
+                .to_response(400)
+                .to_reply(),
+        ),
+    }
+}
+
+/// `/users/2fa/confirm` handler.
+///
+/// Enable the two-factor authentication after verifying a code of the enrolled secret.
+///
+/// # Request body
+/// ```json
+/// {
+///     "code": "287082"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the two-factor authentication was enabled.
+///
+/// ## Errors
+/// 1. The two-factor authentication is already enabled.
+/// 2. You must enroll the two-factor authentication first.
+/// 3. The code is incorrect.
+/// 4. Cannot enable the two-factor authentication.
+pub async fn confirm_handler(
+    authentication: Authentication,
+    body: TotpCodeBody,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
+    let user = match nextchat_database::query(
+        "SELECT totp_secret, totp_enabled, totp_last_step FROM users WHERE id = $1",
+    )
+    .bind(&authentication.user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(user) => user,
+        Err(_) => {
+            return Ok(
+                Error::from_str("Cannot enable the two-factor authentication.")
+                    .to_response(400)
+                    .to_reply(),
+            );
+        }
+    };
+
+    if user.get::<bool, _>("totp_enabled") {
+        return Ok(
+            Error::from_str("The two-factor authentication is already enabled.")
+                .to_response(400)
+                .to_reply(),
+        );
+    }
+
+    let encrypted_secret: String = match user.get::<Option<String>, _>("totp_secret") {
+        Some(encrypted_secret) => encrypted_secret,
+        None => {
+            return Ok(
+                Error::from_str("You must enroll the two-factor authentication first.")
+                    .to_response(400)
+                    .to_reply(),
+            );
+        }
+    };
+
+    if !verify_code(
+        &client,
+        &authentication.user_id,
+        &encrypted_secret,
+        user.get("totp_last_step"),
+        &body.code,
+    )
+    .await
+    {
+        return Ok(Error::from_str("The code is incorrect.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    match nextchat_database::query("UPDATE users SET totp_enabled = true WHERE id = $1")
+        .bind(&authentication.user_id)
+        .execute(&client)
+        .await
+    {
+        Err(_) => Ok(
+            Error::from_str("Cannot enable the two-factor authentication.")
+                .to_response(400)
+                .to_reply(),
+        ),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/users/2fa/disable` handler.
+///
+/// Disable the two-factor authentication and remove the secret.
+///
+/// # Request body
+/// ```json
+/// {
+///     "password": "12345678",
+///     "code": "287082"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the two-factor authentication was disabled.
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. The two-factor authentication is not enabled.
+/// 3. The code is incorrect.
+/// 4. Cannot disable the two-factor authentication.
+/// 5. Too many failed attempts, try again in {seconds} seconds.
+pub async fn disable_handler(
+    authentication: Authentication,
+    body: DisableTotpBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    let user = match nextchat_database::query(
+        "SELECT totp_secret, totp_last_step FROM users WHERE id = $1 AND totp_enabled = true",
+    )
+    .bind(&authentication.user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(user) => user,
+        Err(_) => {
+            return Ok(
+                Error::from_str("The two-factor authentication is not enabled.")
+                    .to_response(400)
+                    .to_reply(),
+            );
+        }
+    };
+
+    let encrypted_secret: String = user
+        .get::<Option<String>, _>("totp_secret")
+        .unwrap_or_default();
+    if !verify_code(
+        &client,
+        &authentication.user_id,
+        &encrypted_secret,
+        user.get("totp_last_step"),
+        &body.code,
+    )
+    .await
+    {
+        return Ok(Error::from_str("The code is incorrect.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    match nextchat_database::query(
+        "UPDATE users SET totp_enabled = false, totp_secret = NULL, totp_last_step = 0 WHERE id = $1",
+    )
+    .bind(&authentication.user_id)
+    .execute(&client)
+    .await
+    {
+        Err(_) => Ok(
+            Error::from_str("Cannot disable the two-factor authentication.")
+                .to_response(400)
+                .to_reply(),
+        ),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/users/signin/2fa` handler.
+///
+/// Complete the sign in of a user with the two-factor authentication enabled using
+/// the challenge returned by `/users/signin`.
+///
+/// # Request body
+/// ```json
+/// {
+///     "challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "code": "287082",
+///     "device_name": "NextChat Desktop"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "user": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "profile_image": "url.png"
+///     },
+///     "access_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "refresh_token": "9fJ0pV2kqHc1..."
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `429` - When the account or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The challenge is invalid or has expired.
+/// 2. Too many failed attempts, try again in {seconds} seconds.
+/// 3. The code is incorrect.
+/// 4. Cannot create the session.
+pub async fn second_factor_handler(
+    body: SecondFactorBody,
+    ip: String,
+    user_agent: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    let user_id: Uuid = match decode_token::<ChallengeClaims>(&body.challenge) {
+        Ok(claims) if claims.purpose == CHALLENGE_PURPOSE => claims.sub,
+        _ => {
+            return Ok(Error::from_str("The challenge is invalid or has expired.")
+                .to_response(401)
+                .to_reply());
+        }
+    };
+
+    let account_key: String = user_id.to_string();
+
+    // Check if the account or the IP address are locked.
+    if let Some(lock_time) = get_lock_time(&storage, &account_key, &ip).await {
+        return Ok(Error::new(format!(
+            "Too many failed attempts, try again in {} seconds.",
+            lock_time.as_secs() + 1
+        ))
+        .to_response(429)
+        .to_reply());
+    }
+
+    let user = match nextchat_database::query(
+        "SELECT id, username, profile_image, totp_secret, totp_last_step FROM users WHERE id = $1 AND totp_enabled = true",
+    )
+    .bind(&user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(user) => user,
+        Err(_) => {
+            return Ok(Error::from_str("The challenge is invalid or has expired.")
+                .to_response(401)
+                .to_reply());
+        }
+    };
+
+    let encrypted_secret: String = user
+        .get::<Option<String>, _>("totp_secret")
+        .unwrap_or_default();
+    if !verify_code(
+        &client,
+        &user_id,
+        &encrypted_secret,
+        user.get("totp_last_step"),
+        &body.code,
+    )
+    .await
+    {
+        add_failed_attempt(&storage, &account_key, &ip).await;
+
+        record_event(
+            &client,
+            AuditEvent::SigninFailed,
+            None,
+            Some(&user_id),
+            Some(&ip),
+            json!({ "method": "totp", "reason": "code" }),
+        )
+        .await;
+
+        return Ok(Error::from_str("The code is incorrect.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    storage
+        .write()
+        .await
+        .get_account_attempts()
+        .reset(&account_key);
+
+    Ok(authentication_reply(
+        &client,
+        UserDataResponse::from_row(&user),
+        None,
+        body.device_name,
+        &user_agent,
+        &ip,
+        AuditEvent::Signin,
+        "totp",
+    )
+    .await)
+}
@@ -8,19 +8,401 @@
 //! `/users/find?username={username}`   -> find_handler
 //! `/users/signup`                     -> signup_handler
 //! `/users/signin`                     -> signin_handler
+//! `/users/password`                   -> change_password_handler
+//! `/users/recovery-codes`             -> recovery_codes_handler
+//! `/users/recover`                    -> recover_handler
+//! `/users/account`                    -> delete_account_handler
 
-use std::convert::Infallible;
+use std::{convert::Infallible, env, time::Duration};
 
-use nextchat_database::{models::users::*, Client, Row, Uuid};
-use nextchat_security::{encrypt_password, verify_password};
+use nextchat_communication::StorageType;
+use nextchat_database::{
+    escape_like,
+    models::{audit::AuditEvent, users::*},
+    Client, NaiveDateTime, Postgres, Row, Transaction, Uuid,
+};
+use nextchat_security::{
+    dummy_verify_password, encrypt_password, format_recovery_code, generate_recovery_code,
+    hash_token, normalize_recovery_code, password_needs_rehash, verify_password, PasswordPolicy,
+};
+use nextchat_utils::{get_username_skeleton, normalize_username, validate_username};
+use serde_json::json;
 use warp::Reply;
 
-use crate::response::{Error, Response};
+use crate::{
+    response::{Error, Response},
+    services::{
+        audit::record_event,
+        authentication::{create_session, revoke_all_sessions, Authentication},
+        invites::{is_invite_only, use_invite},
+        proof_of_work::{use_signup_challenge, verify_signup_challenge},
+        two_factor::challenge_reply,
+    },
+};
+
+/// Number of recovery codes generated for each user.
+const RECOVERY_CODES_COUNT: usize = 10;
+
+/// Default days before deleting an account (`ACCOUNT_DELETION_GRACE_DAYS`).
+const DEFAULT_DELETION_GRACE_DAYS: i32 = 14;
+
+/// Create a new session for the user and get the authentication response.
+///
+/// Banned accounts cannot sign in and signing in cancels the scheduled deletion
+/// of the account. The `event` is written to the audit log with the sign in
+/// `method` (`password`, `totp` or `passkey`).
+pub async fn authentication_reply(
+    client: &Client,
+    user: UserDataResponse,
+    recovery_codes: Option<Vec<String>>,
+    device_name: Option<String>,
+    user_agent: &str,
+    ip: &str,
+    event: AuditEvent,
+    method: &str,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    match nextchat_database::query("SELECT banned_at FROM users WHERE id = $1")
+        .bind(&user.id)
+        .fetch_one(client)
+        .await
+    {
+        Ok(row) if row.get::<Option<NaiveDateTime>, _>("banned_at").is_none() => {}
+        Ok(_) => {
+            record_event(
+                client,
+                AuditEvent::SigninFailed,
+                None,
+                Some(&user.id),
+                Some(ip),
+                json!({ "method": method, "reason": "banned" }),
+            )
+            .await;
+
+            return Error::from_str("The account has been banned.")
+                .to_response(403)
+                .to_reply();
+        }
+        Err(_) => {
+            return Error::from_str("Cannot get the user.")
+                .to_response(400)
+                .to_reply();
+        }
+    }
+
+    if nextchat_database::query(
+        "UPDATE users SET deletion_scheduled_at = NULL WHERE id = $1 AND deletion_scheduled_at IS NOT NULL",
+    )
+    .bind(&user.id)
+    .execute(client)
+    .await
+    .is_err()
+    {
+        return Error::from_str("Cannot cancel the account deletion.")
+            .to_response(400)
+            .to_reply();
+    }
+
+    let metadata = json!({
+        "method": method,
+        "device_name": device_name,
+        "user_agent": user_agent,
+    });
+
+    match create_session(client, &user.id, device_name, user_agent, ip).await {
+        Ok(tokens) => {
+            record_event(
+                client,
+                event,
+                Some(&user.id),
+                Some(&user.id),
+                Some(ip),
+                metadata,
+            )
+            .await;
+
+            Response::new_success(AuthenticationResponse {
+                user,
+                tokens,
+                recovery_codes,
+            })
+            .to_reply()
+        }
+        Err(e) => Error::new(e).to_response(400).to_reply(),
+    }
+}
+
+/// Check the password requirements of `PasswordPolicy::from_env`, the password
+/// cannot contain the username.
+///
+/// The breached passwords list is read from the disk, so the check runs in a
+/// blocking thread.
+async fn validate_password(password: &str, username: &str) -> Result<(), &'static str> {
+    let password: String = String::from(password);
+    let username: String = String::from(username);
+
+    tokio::task::spawn_blocking(move || {
+        PasswordPolicy::from_env().check(&password, &[username.as_str()])
+    })
+    .await
+    .unwrap_or(Err("Cannot check the password."))
+}
+
+/// Encrypt the password in a blocking thread, the Argon2 hash would block the
+/// async runtime.
+async fn encrypt_password_task(password: &str) -> Result<String, String> {
+    let password: String = String::from(password);
+
+    tokio::task::spawn_blocking(move || encrypt_password(&password))
+        .await
+        .unwrap_or_else(|_| Err(String::from("Cannot encrypt the password.")))
+}
+
+/// Verify the password with its hash in a blocking thread, see
+/// `encrypt_password_task`.
+async fn verify_password_task(password: &str, password_hash: &str) -> Result<bool, String> {
+    let password: String = String::from(password);
+    let password_hash: String = String::from(password_hash);
+
+    tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+        .await
+        .unwrap_or_else(|_| Err(String::from("Cannot verify the password.")))
+}
+
+/// Verify the password with the dummy hash in a blocking thread, see
+/// `encrypt_password_task`.
+async fn dummy_verify_password_task(password: &str) {
+    let password: String = String::from(password);
+
+    tokio::task::spawn_blocking(move || dummy_verify_password(&password))
+        .await
+        .ok();
+}
+
+/// Encrypt the password using the current hash parameters and update it in
+/// the database.
+async fn update_password(client: &Client, user_id: &Uuid, password: &str) -> Result<(), String> {
+    let password_hash: String = encrypt_password_task(password).await?;
+
+    match nextchat_database::query("UPDATE users SET password = $1 WHERE id = $2")
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(client)
+        .await
+    {
+        Err(_) => Err(String::from("Cannot update the password.")),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Encrypt the password again with the current secret key and hash parameters.
+async fn rehash_password(client: &Client, user_id: &Uuid, password: &str) {
+    if let Err(e) = update_password(client, user_id, password).await {
+        eprintln!("Cannot rehash the password of the user #{}: {}", user_id, e);
+    }
+}
+
+/// Replace the recovery codes of the user with new codes in a transaction.
+///
+/// The codes are stored hashed, so they are only returned by this function.
+async fn insert_recovery_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> Result<Vec<String>, nextchat_database::Error> {
+    let codes: Vec<String> = (0..RECOVERY_CODES_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let hashes: Vec<String> = codes
+        .iter()
+        .map(|code| hash_token(&normalize_recovery_code(code)))
+        .collect();
+
+    nextchat_database::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    nextchat_database::query(
+        "INSERT INTO recovery_codes(user_id, code) SELECT $1, UNNEST($2::text[])",
+    )
+    .bind(user_id)
+    .bind(&hashes)
+    .execute(&mut *transaction)
+    .await?;
+
+    Ok(codes)
+}
+
+/// Replace the recovery codes of the user with new codes.
+async fn create_recovery_codes(
+    client: &Client,
+    user_id: &Uuid,
+) -> Result<Vec<String>, nextchat_database::Error> {
+    let mut transaction = client.begin().await?;
+    let codes = insert_recovery_codes(&mut transaction, user_id).await?;
+    transaction.commit().await?;
+
+    Ok(codes)
+}
+
+/// Get the remaining lock time of the account or the IP address by failed attempts.
+pub async fn get_lock_time(storage: &StorageType, account_key: &str, ip: &str) -> Option<Duration> {
+    let mut storage = storage.write().await;
+
+    storage
+        .get_account_attempts()
+        .get_lock_time(account_key)
+        .max(storage.get_ip_attempts().get_lock_time(ip))
+}
+
+/// Add a failed attempt to the account and the IP address.
+pub async fn add_failed_attempt(storage: &StorageType, account_key: &str, ip: &str) {
+    let mut storage = storage.write().await;
+
+    storage.get_account_attempts().add_failure(account_key);
+    storage.get_ip_attempts().add_failure(ip);
+}
+
+/// Confirm the password of an authenticated user before a sensitive change.
+///
+/// The failed confirmations share the lockout of the failed signin attempts, by
+/// the user id and the IP address, so a stolen access token cannot be used to
+/// guess the password.
+pub async fn confirm_password(
+    client: &Client,
+    storage: &StorageType,
+    user_id: &Uuid,
+    password: &str,
+    ip: &str,
+) -> Result<(), warp::reply::WithStatus<warp::reply::Json>> {
+    let account_key: String = user_id.to_string();
+
+    if let Some(lock_time) = get_lock_time(storage, &account_key, ip).await {
+        return Err(Error::new(format!(
+            "Too many failed attempts, try again in {} seconds.",
+            lock_time.as_secs() + 1
+        ))
+        .to_response(429)
+        .to_reply());
+    }
+
+    let is_password: bool = match get_password_hash(client, user_id).await {
+        Some(password_hash) => verify_password_task(password, &password_hash)
+            .await
+            .unwrap_or(false),
+        None => false,
+    };
+
+    if !is_password {
+        add_failed_attempt(storage, &account_key, ip).await;
+
+        return Err(Error::from_str("The password is incorrect.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    storage
+        .write()
+        .await
+        .get_account_attempts()
+        .reset(&account_key);
+
+    Ok(())
+}
+
+/// Get the days before deleting an account from the `ACCOUNT_DELETION_GRACE_DAYS`
+/// environment variable. With `0` days the accounts are deleted immediately.
+fn get_deletion_grace_days() -> i32 {
+    env::var("ACCOUNT_DELETION_GRACE_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i32>().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(DEFAULT_DELETION_GRACE_DAYS)
+}
+
+/// Delete a user with its friendships, revoke its sessions and erase the IP
+/// addresses and the metadata of its audit log events.
+///
+/// The sessions, recovery codes and passkeys are deleted by the database.
+pub async fn delete_user(
+    client: &Client,
+    storage: &StorageType,
+    user_id: &Uuid,
+) -> Result<(), nextchat_database::Error> {
+    revoke_all_sessions(client, storage, user_id, None).await?;
+
+    let mut transaction = client.begin().await?;
+
+    nextchat_database::query("DELETE FROM friends WHERE transmitter = $1 OR receiver = $1")
+        .bind(user_id)
+        .execute(&mut transaction)
+        .await?;
+
+    nextchat_database::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut transaction)
+        .await?;
+
+    // The events remain without the personal data of the user.
+    nextchat_database::query(
+        "UPDATE audit_log SET ip = NULL, metadata = '{}' WHERE actor_id = $1 OR target_id = $1",
+    )
+    .bind(user_id)
+    .execute(&mut transaction)
+    .await?;
+
+    transaction.commit().await
+}
+
+/// Delete the accounts whose grace period has ended.
+pub async fn purge_deleted_accounts(client: &Client, storage: &StorageType) {
+    let users = match nextchat_database::query(
+        "SELECT id FROM users WHERE deletion_scheduled_at <= CURRENT_TIMESTAMP",
+    )
+    .fetch_all(client)
+    .await
+    {
+        Ok(users) => users,
+        Err(e) => {
+            eprintln!("Cannot get the accounts scheduled for deletion: {:?}", e);
+            return;
+        }
+    };
+
+    for user in users.iter() {
+        let user_id: Uuid = user.get("id");
+
+        match delete_user(client, storage, &user_id).await {
+            Ok(_) => {
+                record_event(
+                    client,
+                    AuditEvent::AccountDeleted,
+                    None,
+                    Some(&user_id),
+                    None,
+                    json!({ "reason": "grace_period_ended" }),
+                )
+                .await
+            }
+            Err(e) => eprintln!("Cannot delete the user #{}: {:?}", user_id, e),
+        }
+    }
+}
+
+/// Get the password hash of a user by id.
+async fn get_password_hash(client: &Client, user_id: &Uuid) -> Option<String> {
+    nextchat_database::query("SELECT password FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(client)
+        .await
+        .ok()
+        .map(|user| user.get("password"))
+}
 
 /// `/users/all` handler
 ///
 /// # Request query
-/// - `?take={number}` _Default_ 10
+/// - `?take={number}` _Default_ 10, _Max_ 100
 /// - `?skip={number}` _Default_ 0
 ///
 /// ## Example
@@ -35,18 +417,25 @@ use crate::response::{Error, Response};
 /// }
 /// ```
 ///
+/// The users that blocked the authenticated user are not returned.
+///
 /// ## Status codes
 /// - `204` - When the search is successful but does not return anything.
 /// - `200` - When the search is successful and returns one or more results.
 ///
 /// ## Errors
 /// 1. Cannot get the users.
-pub async fn get_all_handler(query: GetAllQuery, client: Client) -> Result<impl Reply, Infallible> {
+pub async fn get_all_handler(
+    authentication: Authentication,
+    query: GetAllQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
     match nextchat_database::query(
-        "SELECT id, username, profile_image FROM users LIMIT $1 OFFSET $2",
+        "SELECT id, username, profile_image FROM users WHERE deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT blocker FROM blocks WHERE blocker = users.id AND blocked = $3) LIMIT $1 OFFSET $2",
     )
-    .bind(query.take.unwrap_or(10))
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
     .bind(query.skip.unwrap_or(0))
+    .bind(&authentication.user_id)
     .fetch_all(&client)
     .await
     {
@@ -66,7 +455,7 @@ pub async fn get_all_handler(query: GetAllQuery, client: Client) -> Result<impl
 /// `/users/search/:text_to_search` handler.
 ///
 /// # Request query
-/// - `?take={number}` _Default_ 10
+/// - `?take={number}` _Default_ 10, _Max_ 100
 /// - `?skip={number}` _Default_ 0
 ///
 /// ## Example
@@ -81,6 +470,8 @@ pub async fn get_all_handler(query: GetAllQuery, client: Client) -> Result<impl
 /// }
 /// ```
 ///
+/// The users that blocked the authenticated user are not returned.
+///
 /// ## Status codes
 /// - `204` - When the search is successful but does not return anything.
 /// - `200` - When the search is successful and returns one or more results.
@@ -89,16 +480,19 @@ pub async fn get_all_handler(query: GetAllQuery, client: Client) -> Result<impl
 /// 1. Cannot get the users.
 pub async fn search_handler(
     text_to_search: String,
+    authentication: Authentication,
     query: SearchQuery,
     client: Client,
 ) -> Result<impl Reply, Infallible> {
-    // Get the search query.
-    let sql = format!("SELECT id, username, profile_image FROM users WHERE username LIKE '{}%' LIMIT $1 OFFSET $2", text_to_search);
-    match nextchat_database::query(&sql)
-        .bind(query.take.unwrap_or(10))
-        .bind(query.skip.unwrap_or(0))
-        .fetch_all(&client)
-        .await
+    match nextchat_database::query(
+        "SELECT id, username, profile_image FROM users WHERE LOWER(username) LIKE LOWER($1) || '%' ESCAPE '\\' AND deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT blocker FROM blocks WHERE blocker = users.id AND blocked = $4) LIMIT $2 OFFSET $3",
+    )
+    .bind(escape_like(&normalize_username(&text_to_search)))
+    .bind(query.take.unwrap_or(10).clamp(1, 100))
+    .bind(query.skip.unwrap_or(0))
+    .bind(&authentication.user_id)
+    .fetch_all(&client)
+    .await
     {
         Err(_) => Ok(Error::from_str("Cannot get the users.")
             .to_response(400)
@@ -128,12 +522,18 @@ pub async fn search_handler(
 /// }
 /// ```
 ///
+/// The users that blocked the authenticated user are not found.
+///
 /// ## Errors
 /// 1. Cannot find a user by id and username at the same time.
 /// 2. Cannot find the user #{user_id}.
 /// 3. Cannot find the user by its name: {username}.
 /// 4. Invalid query.
-pub async fn find_handler(query: FindQuery, client: Client) -> Result<impl Reply, Infallible> {
+pub async fn find_handler(
+    authentication: Authentication,
+    query: FindQuery,
+    client: Client,
+) -> Result<impl Reply, Infallible> {
     if query.id.is_some() && query.username.is_some() {
         Ok(
             Error::from_str("Cannot find a user by id and username at the same time.")
@@ -143,9 +543,10 @@ pub async fn find_handler(query: FindQuery, client: Client) -> Result<impl Reply
     } else if let Some(id) = query.id {
         // Get the user by id.
         match nextchat_database::query(
-            "SELECT id, username, profile_image FROM users WHERE id = $1",
+            "SELECT id, username, profile_image FROM users WHERE id = $1 AND deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT blocker FROM blocks WHERE blocker = users.id AND blocked = $2)",
         )
         .bind(&id)
+        .bind(&authentication.user_id)
         .fetch_one(&client)
         .await
         {
@@ -157,9 +558,10 @@ pub async fn find_handler(query: FindQuery, client: Client) -> Result<impl Reply
     } else if let Some(username) = query.username {
         // Get the user by username.
         match nextchat_database::query(
-            "SELECT id, username, profile_image FROM users username = $1",
+            "SELECT id, username, profile_image FROM users WHERE LOWER(username) = LOWER($1) AND deletion_scheduled_at IS NULL AND NOT EXISTS (SELECT blocker FROM blocks WHERE blocker = users.id AND blocked = $2)",
         )
-        .bind(&username)
+        .bind(normalize_username(&username))
+        .bind(&authentication.user_id)
         .fetch_one(&client)
         .await
         {
@@ -183,64 +585,117 @@ pub async fn find_handler(query: FindQuery, client: Client) -> Result<impl Reply
 /// ```json
 /// {
 ///     "username": "NextChat",
-///     "password": "1234"
+///     "password": "1234",
+///     "device_name": "NextChat Desktop",
+///     "invite_code": "k2vd-q7xm-a4np-3hse",
+///     "pow_challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "pow_solution": "1048576"
 /// }
 /// ```
 ///
 /// ## Requeriments
 /// - `username` **Required** - Min length: 4 - Max length: 15
-/// - `password` **Required** - Min length: 8 - Max length: 40
+/// - `password` **Required** - Min length: 8 - Max length: 128 - Strong and not breached
+/// - `device_name` _Optional_ - Max length: 50
+/// - `invite_code` _Optional_ - Required when the registration is invite-only
+/// - `pow_challenge` _Optional_ - Required when the proof of work is enabled
+/// - `pow_solution` _Optional_ - Required when the proof of work is enabled
 ///
 /// # Response
 /// ```json
 /// {
-///     "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
-///     "username": "NextChat",
-///     "profile_image": "url.png"
+///     "user": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "profile_image": "url.png"
+///     },
+///     "access_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "refresh_token": "9fJ0pV2kqHc1...",
+///     "recovery_codes": ["k2vd-q7xm-a4np-3hse", "..."]
 /// }
 /// ```
 ///
+/// The recovery codes are only returned once, see `/users/recover`.
+///
+/// The username is normalized with Unicode NFKC and must not look like a
+/// reserved name or another user name, see `nextchat_utils::validate_username`.
+///
+/// When the registration is invite-only, `SIGNUP_MODE=invite`, the invite code
+/// is required, see `/users/invites`.
+///
+/// The `pow_challenge` and its `pow_solution` are required unless the proof of
+/// work is disabled, see `/users/signup/challenge`.
+///
 /// ## Errors
 /// 1. You must enter the username.
 /// 2. The username must be between 4 and 15 characteres.
-/// 3. You must enter the password.
-/// 4. The password must be between 8 and 40 characteres.
-/// 5. The username already exists.
-/// 6. Cannot encrypt the password.
-/// 7. Cannot create the user.
-/// 8. Rows not affected.
-/// 9. Unknown.
+/// 3. The username can only contain letters, numbers, `_`, `-` and `.`.
+/// 4. The username must start and end with a letter or a number.
+/// 5. The username cannot mix letters of different alphabets.
+/// 6. The username is reserved.
+/// 7. You must enter the password.
+/// 8. The password is too short, too long, too weak, too common or breached.
+/// 9. You need an invite code to sign up.
+/// 10. You must solve the proof of work challenge.
+/// 11. The challenge is invalid or has expired.
+/// 12. The proof of work solution is incorrect.
+/// 13. The username already exists.
+/// 14. Cannot encrypt the password.
+/// 15. Cannot create the user.
+/// 16. The challenge was already used.
+/// 17. Cannot verify the challenge.
+/// 18. The invite code is invalid or has expired.
+/// 19. Rows not affected.
+/// 20. Unknown.
+/// 21. Cannot create the recovery codes.
+/// 22. Cannot create the session.
 pub async fn signup_handler(
     body: SignUpAndSignInBody,
+    ip: String,
+    user_agent: String,
     client: Client,
 ) -> Result<impl Reply, Infallible> {
-    if body.username.is_empty() {
-        return Ok(Error::from_str("You must enter the username.")
-            .to_response(400)
-            .to_reply());
-    } else if body.username.len() < 4 || body.username.len() > 15 {
-        return Ok(
-            Error::from_str("The username must be between 4 and 15 characteres.")
-                .to_response(400)
-                .to_reply(),
-        );
-    } else if body.password.is_empty() {
-        return Ok(Error::from_str("You must enter the password.")
+    let username: String = match validate_username(&body.username) {
+        Ok(username) => username,
+        Err(e) => {
+            return Ok(Error::new(e).to_response(400).to_reply());
+        }
+    };
+
+    if let Err(e) = validate_password(&body.password, &username).await {
+        return Ok(Error::from_str(e).to_response(400).to_reply());
+    }
+
+    let invite_code: Option<String> = body
+        .invite_code
+        .as_deref()
+        .map(format_recovery_code)
+        .filter(|invite_code| !invite_code.is_empty());
+
+    if invite_code.is_none() && is_invite_only() {
+        return Ok(Error::from_str("You need an invite code to sign up.")
             .to_response(400)
             .to_reply());
-    } else if body.password.len() < 8 || body.password.len() > 40 {
-        return Ok(
-            Error::from_str("The password must be between 8 and 40 characteres.")
-                .to_response(400)
-                .to_reply(),
-        );
     }
 
-    // Check if the username is already in use.
-    match nextchat_database::query("SELECT COUNT(id) AS count FROM users WHERE username = $1")
-        .bind(&body.username)
-        .fetch_one(&client)
-        .await
+    let pow_nonce: Option<String> = match verify_signup_challenge(
+        body.pow_challenge.as_deref(),
+        body.pow_solution.as_deref(),
+    ) {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            return Ok(Error::from_str(e).to_response(400).to_reply());
+        }
+    };
+
+    // Check if the username, or an username that looks alike, is already in use.
+    let username_skeleton: String = get_username_skeleton(&username);
+    match nextchat_database::query(
+        "SELECT COUNT(id) AS count FROM users WHERE username_skeleton = $1",
+    )
+    .bind(&username_skeleton)
+    .fetch_one(&client)
+    .await
     {
         Err(_) => Ok(Error::from_str("Unknown.").to_response(400).to_reply()),
         Ok(with_username) => {
@@ -252,7 +707,7 @@ pub async fn signup_handler(
             }
 
             // Encrypt and get the password.
-            let password: String = match encrypt_password(&body.password) {
+            let password: String = match encrypt_password_task(&body.password).await {
                 Ok(hash) => hash,
                 Err(_) => {
                     return Ok(Error::from_str("Cannot encrypt the password.")
@@ -264,28 +719,76 @@ pub async fn signup_handler(
             // Generate a new UUID.
             let user_id: Uuid = Uuid::new_v4();
 
+            let mut transaction = match client.begin().await {
+                Ok(transaction) => transaction,
+                Err(_) => {
+                    return Ok(Error::from_str("Cannot create the user.")
+                        .to_response(400)
+                        .to_reply());
+                }
+            };
+
+            // The challenge and the invite are only used when the user is created.
+            if let Some(nonce) = &pow_nonce {
+                if let Err(e) = use_signup_challenge(&mut transaction, nonce).await {
+                    return Ok(Error::from_str(e).to_response(400).to_reply());
+                }
+            }
+
+            let invited_by: Option<Uuid> = match &invite_code {
+                Some(invite_code) => match use_invite(&mut transaction, invite_code).await {
+                    Ok(invited_by) => invited_by,
+                    Err(e) => return Ok(Error::from_str(e).to_response(400).to_reply()),
+                },
+                None => None,
+            };
+
             // Add the user to the database.
             match nextchat_database::query(
-                "INSERT INTO users(id, username, password, profile_image) VALUES ($1, $2, $3, $4)",
+                "INSERT INTO users(id, username, username_skeleton, password, profile_image, invited_by) VALUES ($1, $2, $3, $4, $5, $6)",
             )
             .bind(&user_id)
-            .bind(&body.username)
+            .bind(&username)
+            .bind(&username_skeleton)
             .bind(&password)
-            .execute(&client)
+            .bind("")
+            .bind(invited_by)
+            .execute(&mut transaction)
             .await
             {
                 Err(_) => Ok(Error::from_str("Cannot create the user.")
                     .to_response(400)
                     .to_reply()),
                 Ok(result) => {
+                    // The user is only created with its recovery codes.
+                    let recovery_codes = match insert_recovery_codes(&mut transaction, &user_id)
+                        .await
+                    {
+                        Ok(codes) => codes,
+                        Err(_) => {
+                            return Ok(Error::from_str("Cannot create the recovery codes.")
+                                .to_response(400)
+                                .to_reply());
+                        }
+                    };
+
                     // Check if the user was added successfully.
-                    if result.rows_affected() == 1 {
-                        Ok(Response::new_success(UserDataResponse {
-                            id: user_id,
-                            username: body.username,
-                            profile_image: String::new(),
-                        })
-                        .to_reply())
+                    if result.rows_affected() == 1 && transaction.commit().await.is_ok() {
+                        Ok(authentication_reply(
+                            &client,
+                            UserDataResponse {
+                                id: user_id,
+                                username,
+                                profile_image: String::new(),
+                            },
+                            Some(recovery_codes),
+                            body.device_name,
+                            &user_agent,
+                            &ip,
+                            AuditEvent::Signup,
+                            "password",
+                        )
+                        .await)
                     } else {
                         Ok(Error::from_str("Rows not affected.")
                             .to_response(400)
@@ -303,31 +806,54 @@ pub async fn signup_handler(
 /// ```json
 /// {
 ///     "username": "NextChat",
-///     "password": "1234"
+///     "password": "1234",
+///     "device_name": "NextChat Desktop"
 /// }
 /// ```
 ///
 /// ## Requeriments
 /// - `username` **Required**
 /// - `password` **Required**
+/// - `device_name` _Optional_ - Max length: 50
 ///
 /// # Response
 /// ```json
 /// {
-///     "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
-///     "username": "NextChat",
-///     "profile_image": "url.png"
+///     "user": {
+///         "id": "86df7b6c-2377-4cd6-ac1c-badfef243f3b",
+///         "username": "NextChat",
+///         "profile_image": "url.png"
+///     },
+///     "access_token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...",
+///     "refresh_token": "9fJ0pV2kqHc1..."
 /// }
 /// ```
 ///
+/// When the user has the two-factor authentication enabled, the response is a
+/// challenge that must be completed with a code using `/users/signin/2fa`:
+/// ```json
+/// {
+///     "second_factor_required": true,
+///     "challenge": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9..."
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `429` - When the account or the IP address is temporarily locked by failed attempts.
+///
 /// ## Errors
 /// 1. You must enter the username.
 /// 2. You must enter the password.
-/// 3. The username does not exist.
-/// 4. The password is incorrect.
+/// 3. Too many failed attempts, try again in {seconds} seconds.
+/// 4. The username or password is incorrect.
+/// 5. Cannot sign the token.
+/// 6. Cannot create the session.
 pub async fn signin_handler(
     body: SignUpAndSignInBody,
+    ip: String,
+    user_agent: String,
     client: Client,
+    storage: StorageType,
 ) -> Result<impl Reply, Infallible> {
     if body.username.is_empty() {
         return Ok(Error::from_str("You must enter the username.")
@@ -339,35 +865,480 @@ pub async fn signin_handler(
             .to_reply());
     }
 
-    match nextchat_database::query(
-        "SELECT id, password, username, profile_image FROM users WHERE username = $1",
+    let username: String = normalize_username(&body.username);
+    let account_key: String = username.to_lowercase();
+
+    // Check if the account or the IP address are locked.
+    if let Some(lock_time) = get_lock_time(&storage, &account_key, &ip).await {
+        return Ok(Error::new(format!(
+            "Too many failed attempts, try again in {} seconds.",
+            lock_time.as_secs() + 1
+        ))
+        .to_response(429)
+        .to_reply());
+    }
+
+    let user = match nextchat_database::query(
+        "SELECT id, password, username, profile_image, totp_enabled FROM users WHERE LOWER(username) = LOWER($1)",
     )
-    .bind(&body.username)
+    .bind(&username)
     .fetch_one(&client)
     .await
     {
-        Err(_) => Ok(Error::from_str("The username does not exist.")
+        Ok(user) => Some(user),
+        Err(_) => {
+            // Verify a dummy hash, so the response time does not reveal that the user
+            // does not exist.
+            dummy_verify_password_task(&body.password).await;
+            None
+        }
+    };
+
+    let password_hash: Option<&str> = user.as_ref().map(|user| {
+        user.try_get("password")
+            .expect("Cannot parse the user password.")
+    });
+
+    let is_password: bool = match password_hash {
+        Some(password_hash) => match verify_password_task(&body.password, password_hash).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Cannot verify the password of {}: {}", body.username, e);
+                false
+            }
+        },
+        None => false,
+    };
+
+    if !is_password {
+        add_failed_attempt(&storage, &account_key, &ip).await;
+
+        // The attempted username is not recorded, it can contain a password typed in
+        // the wrong field and the audit log cannot be edited.
+        let user_id: Option<Uuid> = user.as_ref().map(|user| user.get("id"));
+        record_event(
+            &client,
+            AuditEvent::SigninFailed,
+            None,
+            user_id.as_ref(),
+            Some(&ip),
+            json!({ "method": "password", "reason": "password" }),
+        )
+        .await;
+
+        return Ok(Error::from_str("The username or password is incorrect.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    storage
+        .write()
+        .await
+        .get_account_attempts()
+        .reset(&account_key);
+
+    let user_row = user.as_ref().expect("Cannot get the user row.");
+    let user = UserDataResponse::from_row(user_row);
+
+    // Encrypt the password again if the hash parameters are outdated.
+    if let Some(password_hash) = password_hash {
+        if password_needs_rehash(password_hash) {
+            rehash_password(&client, &user.id, &body.password).await;
+        }
+    }
+
+    // The session is created after verifying the second factor.
+    if user_row.get::<bool, _>("totp_enabled") {
+        return Ok(challenge_reply(&user.id));
+    }
+
+    Ok(authentication_reply(
+        &client,
+        user,
+        None,
+        body.device_name,
+        &user_agent,
+        &ip,
+        AuditEvent::Signin,
+        "password",
+    )
+    .await)
+}
+
+/// `/users/password` handler.
+///
+/// Change the password of the user and revoke the other sessions.
+///
+/// # Request body
+/// ```json
+/// {
+///     "old_password": "12345678",
+///     "new_password": "87654321"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the password was changed.
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. You must enter the password.
+/// 3. The password is too short, too long, too weak, too common or breached.
+/// 4. Cannot update the password.
+/// 5. Cannot revoke the sessions.
+/// 6. Too many failed attempts, try again in {seconds} seconds.
+pub async fn change_password_handler(
+    authentication: Authentication,
+    body: ChangePasswordBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.old_password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    // The password cannot be checked without the username.
+    let username: String =
+        match nextchat_database::query("SELECT username FROM users WHERE id = $1")
+            .bind(&authentication.user_id)
+            .fetch_one(&client)
+            .await
+        {
+            Ok(user) => user.get("username"),
+            Err(_) => {
+                return Ok(Error::from_str("Cannot update the password.")
+                    .to_response(500)
+                    .to_reply());
+            }
+        };
+
+    if let Err(e) = validate_password(&body.new_password, &username).await {
+        return Ok(Error::from_str(e).to_response(400).to_reply());
+    }
+
+    if let Err(e) = update_password(&client, &authentication.user_id, &body.new_password).await {
+        return Ok(Error::new(e).to_response(400).to_reply());
+    }
+
+    record_event(
+        &client,
+        AuditEvent::PasswordChange,
+        Some(&authentication.user_id),
+        Some(&authentication.user_id),
+        Some(&ip),
+        json!({ "method": "password" }),
+    )
+    .await;
+
+    match revoke_all_sessions(
+        &client,
+        &storage,
+        &authentication.user_id,
+        Some(&authentication.session_id),
+    )
+    .await
+    {
+        Err(_) => Ok(Error::from_str("Cannot revoke the sessions.")
             .to_response(400)
             .to_reply()),
-        Ok(user) => {
-            let is_password: bool = match verify_password(
-                &body.password,
-                user.try_get("password")
-                    .expect("Cannot parse the user password."),
-            ) {
-                Ok(result) => result,
-                Err(e) => {
-                    return Ok(Error::new(format!("{}", e)).to_response(400).to_reply());
-                }
-            };
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
 
-            if !is_password {
-                Ok(Error::from_str("The password is incorrect.")
-                    .to_response(400)
-                    .to_reply())
-            } else {
-                Ok(Response::new_success(UserDataResponse::from_row(&user)).to_reply())
+/// `/users/recovery-codes` handler.
+///
+/// Replace the recovery codes of the user with new codes.
+///
+/// # Request body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "recovery_codes": ["k2vd-q7xm-a4np-3hse", "..."]
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. Cannot create the recovery codes.
+/// 3. Too many failed attempts, try again in {seconds} seconds.
+pub async fn recovery_codes_handler(
+    authentication: Authentication,
+    body: PasswordConfirmationBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    match create_recovery_codes(&client, &authentication.user_id).await {
+        Err(_) => Ok(Error::from_str("Cannot create the recovery codes.")
+            .to_response(400)
+            .to_reply()),
+        Ok(recovery_codes) => {
+            Ok(Response::new_success(RecoveryCodesResponse { recovery_codes }).to_reply())
+        }
+    }
+}
+
+/// `/users/recover` handler.
+///
+/// Reset a forgotten password using a recovery code. The code can only be used
+/// once, all sessions of the user are revoked and the two-factor authentication
+/// is disabled, so a user that lost the authenticator app can sign in again.
+///
+/// # Request body
+/// ```json
+/// {
+///     "username": "NextChat",
+///     "recovery_code": "k2vd-q7xm-a4np-3hse",
+///     "new_password": "87654321"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `204` - When the password was changed.
+/// - `429` - When the account or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. Too many failed attempts, try again in {seconds} seconds.
+/// 2. You must enter the password.
+/// 3. The password is too short, too long, too weak, too common or breached.
+/// 4. The username or recovery code is incorrect.
+/// 5. Cannot update the password.
+/// 6. Cannot revoke the sessions.
+pub async fn recover_handler(
+    body: RecoverBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    let username: String = normalize_username(&body.username);
+    let account_key: String = username.to_lowercase();
+
+    // Check if the account or the IP address are locked.
+    if let Some(lock_time) = get_lock_time(&storage, &account_key, &ip).await {
+        return Ok(Error::new(format!(
+            "Too many failed attempts, try again in {} seconds.",
+            lock_time.as_secs() + 1
+        ))
+        .to_response(429)
+        .to_reply());
+    }
+
+    if let Err(e) = validate_password(&body.new_password, &username).await {
+        return Ok(Error::from_str(e).to_response(400).to_reply());
+    }
+
+    let password_hash: String = match encrypt_password_task(&body.new_password).await {
+        Ok(password_hash) => password_hash,
+        Err(e) => return Ok(Error::new(e).to_response(400).to_reply()),
+    };
+
+    // The code is only used if the password is changed too.
+    let mut transaction = match client.begin().await {
+        Ok(transaction) => transaction,
+        Err(_) => {
+            return Ok(Error::from_str("Cannot update the password.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    // Mark the recovery code as used.
+    let user_id: Uuid = match nextchat_database::query(
+        "UPDATE recovery_codes SET used_at = CURRENT_TIMESTAMP FROM users WHERE recovery_codes.user_id = users.id AND LOWER(users.username) = LOWER($1) AND recovery_codes.code = $2 AND recovery_codes.used_at IS NULL RETURNING users.id",
+    )
+    .bind(&username)
+    .bind(hash_token(&normalize_recovery_code(&body.recovery_code)))
+    .fetch_one(&mut transaction)
+    .await
+    {
+        Ok(user) => user.get("id"),
+        Err(_) => {
+            add_failed_attempt(&storage, &account_key, &ip).await;
+
+            return Ok(Error::from_str("The username or recovery code is incorrect.")
+                .to_response(400)
+                .to_reply());
+        }
+    };
+
+    // Change the password and disable the two-factor authentication.
+    let updated = nextchat_database::query(
+        "UPDATE users SET password = $1, totp_enabled = false, totp_secret = NULL, totp_last_step = 0 WHERE id = $2",
+    )
+    .bind(&password_hash)
+    .bind(&user_id)
+    .execute(&mut transaction)
+    .await;
+
+    if updated.is_err() || transaction.commit().await.is_err() {
+        return Ok(Error::from_str("Cannot update the password.")
+            .to_response(400)
+            .to_reply());
+    }
+
+    storage
+        .write()
+        .await
+        .get_account_attempts()
+        .reset(&account_key);
+
+    record_event(
+        &client,
+        AuditEvent::PasswordChange,
+        None,
+        Some(&user_id),
+        Some(&ip),
+        json!({ "method": "recovery_code" }),
+    )
+    .await;
+
+    match revoke_all_sessions(&client, &storage, &user_id, None).await {
+        Err(_) => Ok(Error::from_str("Cannot revoke the sessions.")
+            .to_response(400)
+            .to_reply()),
+        Ok(_) => Ok(Response::new(204, ()).to_reply()),
+    }
+}
+
+/// `/users/account` handler.
+///
+/// Delete the account of the user. All sessions are revoked immediately and the
+/// account, its friendships and its other data are deleted after the grace period
+/// (`ACCOUNT_DELETION_GRACE_DAYS`, 14 days by default). Signing in again during the
+/// grace period cancels the deletion.
+///
+/// # Request body
+/// ```json
+/// {
+///     "password": "12345678"
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///     "deletion_scheduled_at": "2021-02-16T18:27:08"
+/// }
+/// ```
+///
+/// ## Status codes
+/// - `202` - When the deletion was scheduled.
+/// - `204` - When the account was deleted, the grace period is `0` days.
+/// - `429` - When the user or the IP address is temporarily locked by failed attempts.
+///
+/// ## Errors
+/// 1. The password is incorrect.
+/// 2. Cannot delete the account.
+/// 3. Too many failed attempts, try again in {seconds} seconds.
+pub async fn delete_account_handler(
+    authentication: Authentication,
+    body: PasswordConfirmationBody,
+    ip: String,
+    client: Client,
+    storage: StorageType,
+) -> Result<impl Reply, Infallible> {
+    if let Err(reply) = confirm_password(
+        &client,
+        &storage,
+        &authentication.user_id,
+        &body.password,
+        &ip,
+    )
+    .await
+    {
+        return Ok(reply);
+    }
+
+    let grace_days: i32 = get_deletion_grace_days();
+    if grace_days == 0 {
+        return match delete_user(&client, &storage, &authentication.user_id).await {
+            Err(_) => Ok(Error::from_str("Cannot delete the account.")
+                .to_response(400)
+                .to_reply()),
+            Ok(_) => {
+                // The IP address is not recorded, the personal data of the user was erased.
+                record_event(
+                    &client,
+                    AuditEvent::AccountDeleted,
+                    Some(&authentication.user_id),
+                    Some(&authentication.user_id),
+                    None,
+                    json!({}),
+                )
+                .await;
+
+                Ok(Response::new(204, ()).to_reply())
             }
+        };
+    }
+
+    let deletion_scheduled_at: NaiveDateTime = match nextchat_database::query(
+        "UPDATE users SET deletion_scheduled_at = CURRENT_TIMESTAMP + make_interval(days => $1) WHERE id = $2 RETURNING deletion_scheduled_at",
+    )
+    .bind(grace_days)
+    .bind(&authentication.user_id)
+    .fetch_one(&client)
+    .await
+    {
+        Ok(user) => user.get("deletion_scheduled_at"),
+        Err(_) => {
+            return Ok(Error::from_str("Cannot delete the account.")
+                .to_response(400)
+                .to_reply());
         }
+    };
+
+    record_event(
+        &client,
+        AuditEvent::AccountDeletionScheduled,
+        Some(&authentication.user_id),
+        Some(&authentication.user_id),
+        Some(&ip),
+        json!({ "deletion_scheduled_at": deletion_scheduled_at }),
+    )
+    .await;
+
+    match revoke_all_sessions(&client, &storage, &authentication.user_id, None).await {
+        Err(_) => Ok(Error::from_str("Cannot delete the account.")
+            .to_response(400)
+            .to_reply()),
+        Ok(_) => Ok(Response::new(
+            202,
+            AccountDeletionResponse {
+                deletion_scheduled_at,
+            },
+        )
+        .to_reply()),
     }
 }
@@ -0,0 +1,107 @@
+//! NextChat Utils attempts module.
+//!
+//! This module contains the failed attempts tracker used to lock the signin
+//! temporarily with an exponential backoff.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Lock time after the first failed attempt over the free attempts.
+const BASE_LOCK_TIME: Duration = Duration::from_secs(30);
+
+/// Max lock time (1 hour).
+const MAX_LOCK_TIME: Duration = Duration::from_secs(60 * 60);
+
+/// Time without failed attempts before forgetting a key (1 day).
+const FORGET_TIME: Duration = Duration::from_secs(60 * 60 * 24);
+
+#[derive(Clone)]
+struct AttemptsEntry {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct LoginAttempts {
+    free_attempts: u32,
+    entries: HashMap<String, AttemptsEntry>,
+}
+
+impl LoginAttempts {
+    /// Create a new attempts tracker that allows `free_attempts` failed attempts
+    /// before locking a key.
+    pub fn new(free_attempts: u32) -> Self {
+        Self {
+            free_attempts,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get the remaining lock time of a key, `None` if the key is not locked.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nextchat_utils::LoginAttempts;
+    ///
+    /// fn main() {
+    ///     let mut attempts = LoginAttempts::new(1);
+    ///
+    ///     attempts.add_failure("NextChat");
+    ///     assert!(attempts.get_lock_time("NextChat").is_none());
+    ///
+    ///     attempts.add_failure("NextChat");
+    ///     assert!(attempts.get_lock_time("NextChat").is_some());
+    /// }
+    /// ```
+    pub fn get_lock_time(&self, key: &str) -> Option<Duration> {
+        let locked_until = self.entries.get(key)?.locked_until?;
+        let now = Instant::now();
+
+        if locked_until > now {
+            Some(locked_until - now)
+        } else {
+            None
+        }
+    }
+
+    /// Add a failed attempt to a key and lock it if it has no free attempts.
+    ///
+    /// The lock time is doubled with each failed attempt.
+    pub fn add_failure(&mut self, key: &str) {
+        let now = Instant::now();
+        let entry = self
+            .entries
+            .entry(String::from(key))
+            .or_insert(AttemptsEntry {
+                failures: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+
+        entry.failures += 1;
+        entry.last_failure = now;
+
+        if entry.failures > self.free_attempts {
+            let exponent = (entry.failures - self.free_attempts - 1).min(16);
+            let lock_time = (BASE_LOCK_TIME * 2u32.pow(exponent)).min(MAX_LOCK_TIME);
+
+            entry.locked_until = Some(now + lock_time);
+        }
+    }
+
+    /// Remove the failed attempts of a key.
+    pub fn reset(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// Forget the keys without failed attempts in the last day.
+    pub fn purge(&mut self) {
+        let now = Instant::now();
+
+        self.entries
+            .retain(|_, entry| now.duration_since(entry.last_failure) < FORGET_TIME);
+    }
+}
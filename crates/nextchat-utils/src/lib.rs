@@ -1,6 +1,14 @@
 //! NextChat Utils library.
 
+mod attempts;
+mod username;
 mod versions;
+
+pub use attempts::LoginAttempts;
+pub use username::{
+    get_username_skeleton, normalize_username, validate_username, USERNAME_MAX_LENGTH,
+    USERNAME_MIN_LENGTH,
+};
 pub use versions::{AppVersion, AppVersions};
 
 pub use semver::Version;
@@ -0,0 +1,200 @@
+//! NextChat Utils username module.
+//!
+//! This module contains the username policy: the usernames are normalized with
+//! Unicode NFKC, can only contain letters, numbers and some separators, and
+//! cannot be a reserved name or look like another user name.
+//!
+//! Two usernames look alike when they have the same skeleton, the lowercase
+//! username with the confusable characters replaced by their latin look-alike.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Min length of the usernames in characters.
+pub const USERNAME_MIN_LENGTH: usize = 4;
+
+/// Max length of the usernames in characters.
+pub const USERNAME_MAX_LENGTH: usize = 15;
+
+/// Separators allowed between the letters and numbers.
+const SEPARATORS: [char; 3] = ['_', '-', '.'];
+
+/// Names that cannot be used, compared by skeleton.
+const RESERVED_USERNAMES: [&str; 20] = [
+    "admin",
+    "administrator",
+    "api",
+    "everyone",
+    "help",
+    "mod",
+    "moderator",
+    "nextchat",
+    "nobody",
+    "null",
+    "official",
+    "owner",
+    "root",
+    "security",
+    "server",
+    "staff",
+    "support",
+    "system",
+    "undefined",
+    "user",
+];
+
+/// Alphabets that cannot be mixed in an username, because their letters look alike.
+#[derive(Clone, Copy, PartialEq)]
+enum Alphabet {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+/// Get the alphabet of a letter, `None` for other alphabets.
+fn get_alphabet(character: char) -> Option<Alphabet> {
+    match character {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => Some(Alphabet::Latin),
+        '\u{0370}'..='\u{03FF}' => Some(Alphabet::Greek),
+        '\u{0400}'..='\u{04FF}' => Some(Alphabet::Cyrillic),
+        _ => None,
+    }
+}
+
+/// Get the latin look-alike of a lowercase confusable character.
+fn get_prototype(character: char) -> char {
+    match character {
+        // Cyrillic.
+        'а' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' => 'e',
+        'һ' => 'h',
+        'і' | 'ї' => 'l',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'н' => 'h',
+        'о' => 'o',
+        'р' => 'p',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'х' => 'x',
+        'ѡ' => 'w',
+        // Greek.
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'η' => 'n',
+        'ι' => 'l',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        // Latin, numbers and separators.
+        'i' | 'ı' | '1' | '|' => 'l',
+        '0' => 'o',
+        '3' => 'e',
+        '5' => 's',
+        '-' | '.' => '_',
+        character => character,
+    }
+}
+
+/// Normalize an username with Unicode NFKC and remove the surrounding whitespaces.
+///
+/// # Example
+/// ```rust
+/// use nextchat_utils::normalize_username;
+///
+/// fn main() {
+///     // Fullwidth letters are replaced by the ASCII letters.
+///     assert_eq!(normalize_username(" ＮｅｘｔＣｈａｔ "), "NextChat");
+/// }
+/// ```
+pub fn normalize_username(username: &str) -> String {
+    username.trim().nfkc().collect()
+}
+
+/// Get the skeleton of a normalized username, two usernames with the same
+/// skeleton look alike.
+///
+/// # Example
+/// ```rust
+/// use nextchat_utils::get_username_skeleton;
+///
+/// fn main() {
+///     assert_eq!(get_username_skeleton("Daniel"), get_username_skeleton("daniel"));
+///     assert_eq!(get_username_skeleton("Danie1"), get_username_skeleton("daniel"));
+///     assert_eq!(get_username_skeleton("Dаniеl"), get_username_skeleton("daniel"));
+///     assert_ne!(get_username_skeleton("Daniela"), get_username_skeleton("daniel"));
+/// }
+/// ```
+pub fn get_username_skeleton(username: &str) -> String {
+    username
+        .to_lowercase()
+        .chars()
+        .map(get_prototype)
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+/// Normalize an username and check the username requirements.
+///
+/// Get the normalized username that must be stored, or the reason why it is
+/// not allowed.
+pub fn validate_username(username: &str) -> Result<String, String> {
+    let username: String = normalize_username(username);
+    let length: usize = username.chars().count();
+
+    if username.is_empty() {
+        return Err(String::from("You must enter the username."));
+    } else if length < USERNAME_MIN_LENGTH || length > USERNAME_MAX_LENGTH {
+        return Err(format!(
+            "The username must be between {} and {} characteres.",
+            USERNAME_MIN_LENGTH, USERNAME_MAX_LENGTH
+        ));
+    }
+
+    if !username
+        .chars()
+        .all(|character| character.is_alphanumeric() || SEPARATORS.contains(&character))
+    {
+        return Err(String::from(
+            "The username can only contain letters, numbers, `_`, `-` and `.`.",
+        ));
+    } else if username.starts_with(&SEPARATORS[..]) || username.ends_with(&SEPARATORS[..]) {
+        return Err(String::from(
+            "The username must start and end with a letter or a number.",
+        ));
+    }
+
+    let mut alphabet: Option<Alphabet> = None;
+    for character in username.chars() {
+        if let Some(character_alphabet) = get_alphabet(character) {
+            match alphabet {
+                Some(alphabet) if alphabet != character_alphabet => {
+                    return Err(String::from(
+                        "The username cannot mix letters of different alphabets.",
+                    ));
+                }
+                _ => alphabet = Some(character_alphabet),
+            }
+        }
+    }
+
+    let skeleton: String = get_username_skeleton(&username);
+    if RESERVED_USERNAMES
+        .iter()
+        .any(|reserved| get_username_skeleton(reserved) == skeleton)
+    {
+        return Err(String::from("The username is reserved."));
+    }
+
+    Ok(username)
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use nextchat_utils::LoginAttempts;
+
+#[test]
+fn test_attempts_lock() {
+    let mut attempts = LoginAttempts::new(3);
+
+    for _ in 0..3 {
+        attempts.add_failure("NextChat");
+        assert!(attempts.get_lock_time("NextChat").is_none());
+    }
+
+    attempts.add_failure("NextChat");
+    let lock_time = attempts.get_lock_time("NextChat");
+    assert!(lock_time.is_some());
+    assert!(lock_time.unwrap() <= Duration::from_secs(30));
+    assert!(lock_time.unwrap() > Duration::from_secs(29));
+
+    // The lock time is doubled.
+    attempts.add_failure("NextChat");
+    assert!(attempts.get_lock_time("NextChat").unwrap() > Duration::from_secs(59));
+
+    // Other keys are not locked.
+    assert!(attempts.get_lock_time("Other").is_none());
+
+    attempts.reset("NextChat");
+    assert!(attempts.get_lock_time("NextChat").is_none());
+}
+
+#[test]
+fn test_attempts_max_lock_time() {
+    let mut attempts = LoginAttempts::new(0);
+
+    for _ in 0..40 {
+        attempts.add_failure("NextChat");
+    }
+
+    assert!(attempts.get_lock_time("NextChat").unwrap() <= Duration::from_secs(60 * 60));
+}
+
+#[test]
+fn test_attempts_purge() {
+    let mut attempts = LoginAttempts::new(0);
+
+    attempts.add_failure("NextChat");
+
+    // The recent failed attempts are not forgotten.
+    attempts.purge();
+    assert!(attempts.get_lock_time("NextChat").is_some());
+}
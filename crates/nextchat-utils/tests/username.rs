@@ -0,0 +1,90 @@
+use nextchat_utils::{
+    get_username_skeleton, validate_username, USERNAME_MAX_LENGTH, USERNAME_MIN_LENGTH,
+};
+
+#[test]
+fn test_username_normalization() {
+    assert_eq!(
+        validate_username("  NextChat_01 "),
+        Ok(String::from("NextChat_01"))
+    );
+    assert_eq!(
+        validate_username("ｄａｎｉｅｌ"),
+        Ok(String::from("daniel"))
+    );
+
+    // The length is measured in characters.
+    assert_eq!(validate_username("ñandú"), Ok(String::from("ñandú")));
+    assert!(validate_username("ñañ").is_err());
+    assert!(validate_username("ññññññññññññññññ").is_err());
+}
+
+#[test]
+fn test_username_characters() {
+    assert!(validate_username("").is_err());
+    assert!(validate_username("next chat").is_err());
+    assert!(validate_username("next@chat").is_err());
+    assert!(validate_username("_nextchat").is_err());
+    assert!(validate_username("nextchat.").is_err());
+    assert!(validate_username("next.chat-01").is_ok());
+    assert!(validate_username("Даниил").is_ok());
+}
+
+#[test]
+fn test_username_confusables() {
+    // Latin and cyrillic letters mixed.
+    assert!(validate_username("Dаniel").is_err());
+
+    assert_eq!(
+        get_username_skeleton("Daniel"),
+        get_username_skeleton("DANIEL")
+    );
+    assert_eq!(
+        get_username_skeleton("paypal"),
+        get_username_skeleton("pаypаl")
+    );
+    assert_eq!(
+        get_username_skeleton("modern"),
+        get_username_skeleton("modem")
+    );
+    assert_eq!(
+        get_username_skeleton("lnvite"),
+        get_username_skeleton("Invite")
+    );
+    assert_ne!(
+        get_username_skeleton("daniel"),
+        get_username_skeleton("danie")
+    );
+}
+
+#[test]
+fn test_username_reserved() {
+    assert_eq!(
+        validate_username("admin"),
+        Err(String::from("The username is reserved."))
+    );
+    assert_eq!(
+        validate_username("Adm1n"),
+        Err(String::from("The username is reserved."))
+    );
+    assert_eq!(
+        validate_username("NextChat"),
+        Err(String::from("The username is reserved."))
+    );
+    assert_eq!(
+        validate_username("system"),
+        Err(String::from("The username is reserved."))
+    );
+    assert!(validate_username("administrador").is_ok());
+}
+
+#[test]
+fn test_username_length_message() {
+    assert_eq!(
+        validate_username("abc"),
+        Err(format!(
+            "The username must be between {} and {} characteres.",
+            USERNAME_MIN_LENGTH, USERNAME_MAX_LENGTH
+        ))
+    );
+}
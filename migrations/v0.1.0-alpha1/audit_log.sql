@@ -0,0 +1,42 @@
+CREATE TYPE audit_event AS ENUM
+(
+    'signup',
+    'signin',
+    'signin_failed',
+    'password_change',
+    'role_change',
+    'ban',
+    'unban',
+    'account_deletion_scheduled',
+    'account_deleted'
+);
+
+-- The users are not referenced, so the events remain after deleting the users.
+CREATE TABLE IF NOT EXISTS audit_log
+(
+    id              BIGSERIAL   NOT NULL PRIMARY KEY,
+    event           audit_event NOT NULL,
+
+    actor_id        uuid,
+    target_id       uuid,
+    ip              TEXT,
+    metadata        JSONB       NOT NULL DEFAULT '{}',
+
+    created_at      TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_actor_id ON audit_log (actor_id, created_at);
+CREATE INDEX IF NOT EXISTS audit_log_target_id ON audit_log (target_id, created_at);
+
+-- The audit log is append-only, the only allowed update is the erasure of the IP address and
+-- the metadata of the events of a deleted user.
+CREATE OR REPLACE RULE audit_log_no_update AS ON UPDATE TO audit_log
+    WHERE NOT (
+        NEW.ip IS NULL AND NEW.metadata = '{}'
+        AND NEW.id = OLD.id AND NEW.event = OLD.event
+        AND NEW.actor_id IS NOT DISTINCT FROM OLD.actor_id
+        AND NEW.target_id IS NOT DISTINCT FROM OLD.target_id
+        AND NEW.created_at = OLD.created_at
+    )
+    DO INSTEAD NOTHING;
+CREATE OR REPLACE RULE audit_log_no_delete AS ON DELETE TO audit_log DO INSTEAD NOTHING;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS blocks
+(
+    blocker     uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    blocked     uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at  TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (blocker, blocked),
+    CHECK (blocker <> blocked)
+);
+
+CREATE INDEX IF NOT EXISTS blocks_blocked ON blocks (blocked);
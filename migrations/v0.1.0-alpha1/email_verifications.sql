@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS email_verifications
+(
+    token           TEXT            NOT NULL PRIMARY KEY,
+    user_id         uuid            NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    email           VARCHAR(254)    NOT NULL,
+
+    created_at      TIMESTAMP       NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at      TIMESTAMP       NOT NULL
+);
@@ -6,8 +6,14 @@ CREATE TYPE friends_state AS ENUM
 
 CREATE TABLE IF NOT EXISTS friends
 (
-    transmitter uuid          NOT NULL,
-    receiver    uuid          NOT NULL,
+    transmitter uuid          NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    receiver    uuid          NOT NULL REFERENCES users (id) ON DELETE CASCADE,
     state       friends_state NOT NULL,
-    since       TIMESTAMP     NOT NULL DEFAULT CURRENT_TIMESTAMP
+    since       TIMESTAMP     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CHECK (transmitter <> receiver)
 );
+
+-- Only one friendship or request between two users, in either direction.
+CREATE UNIQUE INDEX IF NOT EXISTS friends_pair ON friends (LEAST(transmitter, receiver), GREATEST(transmitter, receiver));
+CREATE INDEX IF NOT EXISTS friends_receiver ON friends (receiver);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS invites
+(
+    code            TEXT        NOT NULL PRIMARY KEY,
+    created_by      uuid        REFERENCES users (id) ON DELETE SET NULL,
+
+    max_uses        INTEGER     NOT NULL DEFAULT 1,
+    uses            INTEGER     NOT NULL DEFAULT 0,
+
+    expires_at      TIMESTAMP,
+    created_at      TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS invites_created_by ON invites (created_by);
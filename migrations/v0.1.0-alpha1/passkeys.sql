@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS passkeys
+(
+    id              uuid        NOT NULL DEFAULT uuid_generate_v4 () PRIMARY KEY,
+    user_id         uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    credential_id   TEXT        NOT NULL UNIQUE,
+    credential      TEXT        NOT NULL,
+
+    name            VARCHAR(50) NOT NULL,
+
+    created_at      TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used       TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
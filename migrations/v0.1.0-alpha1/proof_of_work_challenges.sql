@@ -0,0 +1,7 @@
+-- The challenges are signed tokens, only the used ones are stored to reject the
+-- replays until they expire.
+CREATE TABLE IF NOT EXISTS proof_of_work_challenges
+(
+    nonce           TEXT        NOT NULL PRIMARY KEY,
+    expires_at      TIMESTAMP   NOT NULL
+);
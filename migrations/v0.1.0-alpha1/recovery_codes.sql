@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS recovery_codes
+(
+    user_id     uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code        TEXT        NOT NULL,
+    used_at     TIMESTAMP,
+    created_at  TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (user_id, code)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS sessions
+(
+    id              uuid        NOT NULL DEFAULT uuid_generate_v4 () PRIMARY KEY,
+    user_id         uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    refresh_token   TEXT        NOT NULL UNIQUE,
+
+    device_name     VARCHAR(50) NOT NULL,
+    user_agent      TEXT        NOT NULL,
+    ip              TEXT        NOT NULL,
+
+    created_at      TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used       TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -1,12 +1,43 @@
+CREATE TYPE user_role AS ENUM
+(
+    'user',
+    'moderator',
+    'admin'
+);
+
 CREATE TABLE IF NOT EXISTS users
 (
     id              uuid        NOT NULL DEFAULT uuid_generate_v4 () PRIMARY KEY,
-    username        VARCHAR(15) NOT NULL UNIQUE,
+    username        VARCHAR(15) NOT NULL,
+    username_skeleton   TEXT    NOT NULL UNIQUE,
     password        TEXT        NOT NULL,
 
+    email               VARCHAR(254),
+    email_verified_at   TIMESTAMP,
+    last_verification_sent_at   TIMESTAMP,
+
     profile_image   TEXT        NOT NULL,
+    role            user_role   NOT NULL DEFAULT 'user',
+
+    totp_secret     TEXT,
+    totp_enabled    BOOLEAN     NOT NULL DEFAULT false,
+    totp_last_step  BIGINT      NOT NULL DEFAULT 0,
+
+    invited_by              uuid        REFERENCES users (id) ON DELETE SET NULL,
+
+    banned_at               TIMESTAMP,
+    ban_reason              TEXT,
+
+    deletion_scheduled_at   TIMESTAMP,
+    last_export_at          TIMESTAMP,
 
     logged          BOOLEAN     NOT NULL DEFAULT false,
     last_online     TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     created_at      TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
+
+-- The usernames are unique without case distinction.
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower ON users (LOWER(username));
+
+-- Only one account can verify each email.
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower ON users (LOWER(email)) WHERE email_verified_at IS NOT NULL;